@@ -0,0 +1,267 @@
+use std::{fs, io, path::Path};
+
+use serde_json::{json, Map, Value};
+
+use ccwc::wc::Counts;
+
+use crate::output::{csv_field, Column, Format, Row};
+
+// The counts of a run, saved so a later run can be compared against them. It is written as
+// `{"files": {"a.txt": {"lines": 3, ...}, ...}, "total": {...}}`, with the columns that were
+// reported.
+#[derive(Debug, PartialEq)]
+pub struct Baseline {
+    files: Map<String, Value>,
+    total: Map<String, Value>,
+}
+
+impl Baseline {
+    // The standard input is saved under the name "-".
+    pub fn new(rows: &[Row], total: &Counts, columns: &[Column]) -> Baseline {
+        let metrics = |counts: &Counts| -> Map<String, Value> { columns.iter().map(|column| (column.label().to_string(), column.value(counts))).collect() };
+        Baseline {
+            files: rows.iter().map(|row| (row.name.clone().unwrap_or_else(|| "-".to_string()), Value::Object(metrics(&row.counts)))).collect(),
+            total: metrics(total),
+        }
+    }
+
+    pub fn load(path: &Path) -> io::Result<Baseline> {
+        let invalid = |message: &str| io::Error::new(io::ErrorKind::InvalidData, format!("not a baseline: {}", message));
+        let document: Value = serde_json::from_slice(&fs::read(path)?).map_err(|error| invalid(&error.to_string()))?;
+        let object = |key: &str| document.get(key).and_then(Value::as_object).cloned().ok_or_else(|| invalid(&format!("no \"{}\" object", key)));
+        Ok(Baseline { files: object("files")?, total: object("total")? })
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let document = json!({ "files": self.files, "total": self.total });
+        let mut output = serde_json::to_string_pretty(&document).unwrap();
+        output.push('\n');
+        fs::write(path, output)
+    }
+
+    // What changed since `baseline`. Only the metrics in both runs are compared.
+    pub fn compare(&self, baseline: &Baseline) -> Comparison {
+        let mut comparison = Comparison::default();
+        for (name, metrics) in &self.files {
+            match baseline.files.get(name) {
+                Some(old) => comparison.changed.extend(FileDelta::new(name, old, metrics)),
+                None => comparison.added.push(name.clone()),
+            }
+        }
+        comparison.removed = baseline.files.keys().filter(|name| !self.files.contains_key(*name)).cloned().collect();
+        comparison.total = FileDelta::new("total", &Value::Object(baseline.total.clone()), &Value::Object(self.total.clone()));
+        comparison
+    }
+}
+
+pub struct MetricDelta {
+    pub label: String,
+    pub baseline: Value,
+    pub current: Value,
+}
+
+impl MetricDelta {
+    // How much the metric grew, as a percentage of the baseline. None when it grew from nothing.
+    pub fn growth_percent(&self) -> Option<f64> {
+        let baseline = self.baseline.as_f64().unwrap_or_default();
+        let current = self.current.as_f64().unwrap_or_default();
+        if baseline == 0.0 {
+            return (current == 0.0).then_some(0.0);
+        }
+        Some((current - baseline) / baseline * 100.0)
+    }
+
+    fn change_text(&self) -> String {
+        match self.growth_percent() {
+            Some(percent) => format!("{:+.1}%", percent),
+            None => "new".to_string(),
+        }
+    }
+}
+
+// The metrics of one file that are different from the baseline.
+pub struct FileDelta {
+    pub name: String,
+    pub metrics: Vec<MetricDelta>,
+}
+
+impl FileDelta {
+    // None when nothing changed.
+    fn new(name: &str, baseline: &Value, current: &Value) -> Option<FileDelta> {
+        let (Some(baseline), Some(current)) = (baseline.as_object(), current.as_object()) else {
+            return None;
+        };
+        let metrics: Vec<MetricDelta> = current
+            .iter()
+            .filter_map(|(label, value)| {
+                let old = baseline.get(label)?;
+                (old.as_f64() != value.as_f64()).then(|| MetricDelta { label: label.clone(), baseline: old.clone(), current: value.clone() })
+            })
+            .collect();
+        (!metrics.is_empty()).then(|| FileDelta { name: name.to_string(), metrics })
+    }
+}
+
+#[derive(Default)]
+pub struct Comparison {
+    pub changed: Vec<FileDelta>,
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub total: Option<FileDelta>,
+}
+
+impl Comparison {
+    // The metrics that grew by more than `percent`, including any that grew from nothing.
+    pub fn growth_above(&self, percent: f64) -> Vec<(&str, &MetricDelta)> {
+        self.changed
+            .iter()
+            .chain(&self.total)
+            .flat_map(|file| file.metrics.iter().map(move |metric| (file.name.as_str(), metric)))
+            .filter(|(_, metric)| metric.growth_percent().is_none_or(|growth| growth > percent))
+            .collect()
+    }
+
+    pub fn render(&self, format: Format) -> String {
+        match format {
+            Format::Table => self.table(),
+            Format::Json => self.json(),
+            Format::Csv => self.csv(),
+        }
+    }
+
+    // A line per file that changed, was added or was removed, with the total last.
+    fn table(&self) -> String {
+        let metrics = |file: &FileDelta| -> String {
+            let metrics: Vec<String> = file
+                .metrics
+                .iter()
+                .map(|metric| format!("{} {} -> {} ({})", metric.label, metric.baseline, metric.current, metric.change_text()))
+                .collect();
+            metrics.join(", ")
+        };
+        let mut output = String::new();
+        for file in &self.changed {
+            output.push_str(&format!("changed {}: {}\n", file.name, metrics(file)));
+        }
+        for name in &self.added {
+            output.push_str(&format!("added   {}\n", name));
+        }
+        for name in &self.removed {
+            output.push_str(&format!("removed {}\n", name));
+        }
+        if let Some(total) = &self.total {
+            output.push_str(&format!("total   {}\n", metrics(total)));
+        }
+        output
+    }
+
+    // `{"changed": [{"file": ..., "metrics": {"lines": {"baseline": ..., "current": ..., "change_percent": ...}}}], "added": [...], "removed": [...], "total": {...}}`.
+    // `change_percent` is null for a metric that grew from nothing.
+    fn json(&self) -> String {
+        let metrics = |file: &FileDelta| -> Map<String, Value> {
+            file.metrics
+                .iter()
+                .map(|metric| {
+                    let change = metric.growth_percent().map(|percent| (percent * 10.0).round() / 10.0);
+                    (metric.label.clone(), json!({ "baseline": metric.baseline, "current": metric.current, "change_percent": change }))
+                })
+                .collect()
+        };
+        let changed: Vec<Value> = self.changed.iter().map(|file| json!({ "file": file.name, "metrics": metrics(file) })).collect();
+        let total = self.total.as_ref().map(metrics).unwrap_or_default();
+        let document = json!({ "changed": changed, "added": self.added, "removed": self.removed, "total": total });
+
+        let mut output = serde_json::to_string_pretty(&document).unwrap();
+        output.push('\n');
+        output
+    }
+
+    // A row per changed metric, and a row with no metric for each file added or removed.
+    fn csv(&self) -> String {
+        let mut output = "status,file,metric,baseline,current,change_percent\n".to_string();
+        for file in &self.changed {
+            for metric in &file.metrics {
+                let change = metric.growth_percent().map(|percent| format!("{:.1}", percent)).unwrap_or_default();
+                output.push_str(&format!("changed,{},{},{},{},{}\n", csv_field(&file.name), metric.label, metric.baseline, metric.current, change));
+            }
+        }
+        for name in &self.added {
+            output.push_str(&format!("added,{},,,,\n", csv_field(name)));
+        }
+        for name in &self.removed {
+            output.push_str(&format!("removed,{},,,,\n", csv_field(name)));
+        }
+        for metric in self.total.iter().flat_map(|total| &total.metrics) {
+            let change = metric.growth_percent().map(|percent| format!("{:.1}", percent)).unwrap_or_default();
+            output.push_str(&format!("changed,total,{},{},{},{}\n", metric.label, metric.baseline, metric.current, change));
+        }
+        output
+    }
+}
+
+#[cfg(test)]
+mod baseline_tests {
+    use super::*;
+
+    const COLUMNS: [Column; 2] = [Column::Lines, Column::Bytes];
+
+    fn baseline(files: &[(&str, u64, u64)]) -> Baseline {
+        let rows: Vec<Row> = files
+            .iter()
+            .map(|&(name, lines, bytes)| Row { name: Some(name.to_string()), counts: Counts { line_count: lines, byte_count: bytes, ..Counts::default() } })
+            .collect();
+        let mut total = Counts::default();
+        for row in &rows {
+            total += row.counts.clone();
+        }
+        Baseline::new(&rows, &total, &COLUMNS)
+    }
+
+    #[test]
+    fn save_and_load_round_trip() {
+        // Arrange
+        let path = std::env::temp_dir().join(format!("ccwc-baseline-{}.json", std::process::id()));
+        let saved = baseline(&[("a.txt", 3, 70), ("b.txt", 1, 9)]);
+
+        //Act
+        saved.save(&path).unwrap();
+        let loaded = Baseline::load(&path).unwrap();
+
+        //Assert
+        assert_eq!(loaded, saved);
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn compare_finds_changed_added_and_removed_files() {
+        // Arrange
+        let old = baseline(&[("a.txt", 10, 100), ("b.txt", 1, 9), ("gone.txt", 2, 20)]);
+        let new = baseline(&[("a.txt", 12, 90), ("b.txt", 1, 9), ("new.txt", 0, 0)]);
+
+        //Act
+        let comparison = new.compare(&old);
+
+        //Assert
+        assert_eq!(
+            comparison.render(Format::Table),
+            "changed a.txt: lines 10 -> 12 (+20.0%), bytes 100 -> 90 (-10.0%)\n\
+             added   new.txt\n\
+             removed gone.txt\n\
+             total   bytes 129 -> 99 (-23.3%)\n"
+        );
+    }
+
+    #[test]
+    fn growth_above_threshold() {
+        // Arrange
+        let old = baseline(&[("a.txt", 10, 100), ("b.txt", 0, 0)]);
+        let new = baseline(&[("a.txt", 12, 104), ("b.txt", 1, 5)]);
+        let comparison = new.compare(&old);
+
+        //Act
+        let growth: Vec<(&str, &str)> = comparison.growth_above(10.0).into_iter().map(|(name, metric)| (name, metric.label.as_str())).collect();
+
+        //Assert
+        assert_eq!(growth, vec![("a.txt", "lines"), ("b.txt", "lines"), ("b.txt", "bytes"), ("total", "lines")]);
+    }
+}
@@ -0,0 +1,169 @@
+use std::{
+    cell::Cell,
+    fs::File,
+    io::{self, Cursor, Read},
+    path::Path,
+    rc::Rc,
+};
+
+use bzip2::read::MultiBzDecoder;
+use flate2::read::MultiGzDecoder;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Compression {
+    Gzip,
+    Zstd,
+    Bzip2,
+}
+
+impl Compression {
+    // The compression named by the magic bytes at the start of `bytes`.
+    fn sniff(bytes: &[u8]) -> Option<Compression> {
+        match bytes {
+            [0x1F, 0x8B, ..] => Some(Compression::Gzip),
+            [0x28, 0xB5, 0x2F, 0xFD, ..] => Some(Compression::Zstd),
+            [b'B', b'Z', b'h', ..] => Some(Compression::Bzip2),
+            _ => None,
+        }
+    }
+}
+
+// Whether inputs are decompressed before they are counted.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Decompress {
+    // When the input starts with the magic bytes of a compressed format.
+    #[default]
+    Auto,
+    Never,
+    // Always, whatever the input starts with.
+    Always(Compression),
+}
+
+// An input ready to be counted. `raw_bytes` is the number of bytes read from the input before
+// they were decompressed, and is only final once the reader has been read to the end.
+pub struct Input<'a> {
+    pub reader: Box<dyn Read + 'a>,
+    pub compression: Option<Compression>,
+    pub raw_bytes: Rc<Cell<u64>>,
+}
+
+// Wraps a reader in the decompressor for its format. The magic bytes are read to find the format
+// and then put back in front of the rest of the input, so this works on a pipe as well as a file.
+pub fn open<'a, R: Read + 'a>(reader: R, decompress: Decompress) -> io::Result<Input<'a>> {
+    let raw_bytes = Rc::new(Cell::new(0));
+    let mut reader = CountingReader { inner: reader, count: Rc::clone(&raw_bytes) };
+
+    let mut magic = Vec::with_capacity(4);
+    if decompress == Decompress::Auto {
+        (&mut reader).take(4).read_to_end(&mut magic)?;
+    }
+    let compression = match decompress {
+        Decompress::Auto => Compression::sniff(&magic),
+        Decompress::Never => None,
+        Decompress::Always(compression) => Some(compression),
+    };
+
+    let reader = Cursor::new(magic).chain(reader);
+    let reader: Box<dyn Read + 'a> = match compression {
+        None => Box::new(reader),
+        Some(Compression::Gzip) => Box::new(MultiGzDecoder::new(reader)),
+        Some(Compression::Zstd) => Box::new(zstd::stream::read::Decoder::new(reader)?),
+        Some(Compression::Bzip2) => Box::new(MultiBzDecoder::new(reader)),
+    };
+    Ok(Input { reader, compression, raw_bytes })
+}
+
+// The compression of a file, without reading more than its magic bytes.
+pub fn detect(path: &Path, decompress: Decompress) -> io::Result<Option<Compression>> {
+    Ok(open(File::open(path)?, decompress)?.compression)
+}
+
+struct CountingReader<R> {
+    inner: R,
+    count: Rc<Cell<u64>>,
+}
+
+impl<R: Read> Read for CountingReader<R> {
+    fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+        let read_count = self.inner.read(buffer)?;
+        self.count.set(self.count.get() + read_count as u64);
+        Ok(read_count)
+    }
+}
+
+#[cfg(test)]
+mod decompress_tests {
+    use super::*;
+    use std::io::Write;
+
+    const TEXT: &[u8] = b"one two three\nfour five\n";
+
+    fn gzip(data: &[u8]) -> Vec<u8> {
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    fn bzip2(data: &[u8]) -> Vec<u8> {
+        let mut encoder = bzip2::write::BzEncoder::new(Vec::new(), bzip2::Compression::default());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    fn read_all(data: &[u8], decompress: Decompress) -> (Vec<u8>, Option<Compression>, u64) {
+        let mut input = open(data, decompress).unwrap();
+        let mut output = Vec::new();
+        input.reader.read_to_end(&mut output).unwrap();
+        (output, input.compression, input.raw_bytes.get())
+    }
+
+    #[test]
+    fn formats_are_detected_by_magic_bytes() {
+        // Arrange
+        let inputs = [
+            (gzip(TEXT), Some(Compression::Gzip)),
+            (zstd::encode_all(TEXT, 0).unwrap(), Some(Compression::Zstd)),
+            (bzip2(TEXT), Some(Compression::Bzip2)),
+            (TEXT.to_vec(), None),
+        ];
+
+        for (data, expected) in inputs {
+            //Act
+            let (output, compression, raw_bytes) = read_all(&data, Decompress::Auto);
+
+            //Assert
+            assert_eq!(output, TEXT);
+            assert_eq!(compression, expected);
+            assert_eq!(raw_bytes, data.len() as u64);
+        }
+    }
+
+    #[test]
+    fn concatenated_gzip_members_are_all_read() {
+        // Arrange
+        let mut data = gzip(b"first\n");
+        data.extend(gzip(b"second\n"));
+
+        //Act
+        let (output, _, _) = read_all(&data, Decompress::Auto);
+
+        //Assert
+        assert_eq!(output, b"first\nsecond\n");
+    }
+
+    #[test]
+    fn decompress_overrides_detection() {
+        // Arrange
+        let data = gzip(TEXT);
+
+        //Act
+        let (never, compression, _) = read_all(&data, Decompress::Never);
+        let mut forced = open(TEXT, Decompress::Always(Compression::Gzip)).unwrap();
+        let error = forced.reader.read_to_end(&mut Vec::new()).unwrap_err();
+
+        //Assert
+        assert_eq!(never, data);
+        assert_eq!(compression, None);
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+    }
+}
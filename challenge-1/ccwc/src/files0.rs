@@ -0,0 +1,89 @@
+use std::{
+    io::{self, BufRead},
+    path::PathBuf,
+};
+
+// A name read from a `--files0-from` list. `number` counts names from 1, for diagnostics, and
+// `path` is None when the name was empty.
+pub struct FileName {
+    pub number: usize,
+    pub path: Option<PathBuf>,
+}
+
+// Reads NUL separated file names, as written by `find -print0`, one at a time so the list is
+// never held in memory. A trailing NUL does not add an empty name.
+pub fn file_names<R: BufRead>(reader: R) -> impl Iterator<Item = io::Result<FileName>> {
+    reader.split(b'\0').enumerate().map(|(index, name)| {
+        let name = name?;
+        Ok(FileName {
+            number: index + 1,
+            path: (!name.is_empty()).then(|| path_from_bytes(name)),
+        })
+    })
+}
+
+#[cfg(unix)]
+fn path_from_bytes(bytes: Vec<u8>) -> PathBuf {
+    use std::{ffi::OsString, os::unix::ffi::OsStringExt};
+    PathBuf::from(OsString::from_vec(bytes))
+}
+
+#[cfg(not(unix))]
+fn path_from_bytes(bytes: Vec<u8>) -> PathBuf {
+    PathBuf::from(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+#[cfg(test)]
+mod file_names_tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn read(input: &[u8]) -> Vec<(usize, Option<String>)> {
+        file_names(Cursor::new(input.to_vec()))
+            .map(|name| {
+                let name = name.unwrap();
+                (name.number, name.path.map(|path| path.to_string_lossy().into_owned()))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn file_names_split_on_nul() {
+        // Arrange
+        let input = b"a.txt\0dir/with space.txt\0new\nline.txt\0";
+
+        //Act
+        let names = read(input);
+
+        //Assert
+        assert_eq!(names, vec![
+            (1, Some("a.txt".to_string())),
+            (2, Some("dir/with space.txt".to_string())),
+            (3, Some("new\nline.txt".to_string())),
+        ]);
+    }
+
+    #[test]
+    fn file_names_reports_empty_names() {
+        // Arrange
+        let input = b"a.txt\0\0b.txt";
+
+        //Act
+        let names = read(input);
+
+        //Assert
+        assert_eq!(names, vec![(1, Some("a.txt".to_string())), (2, None), (3, Some("b.txt".to_string()))]);
+    }
+
+    #[test]
+    fn file_names_empty_list() {
+        // Arrange
+        let input = b"";
+
+        //Act
+        let names = read(input);
+
+        //Assert
+        assert!(names.is_empty());
+    }
+}
@@ -0,0 +1,182 @@
+use std::{
+    fs::{self, File, Metadata},
+    io::{self, Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+};
+
+use ccwc::wc::{Counter, Counts, Options};
+
+// Why the counts in an update changed.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Change {
+    Opened,
+    Appended,
+    // The file is now shorter than what has already been counted, so it is counted again from the start.
+    Truncated,
+    // The path now names a different file, for example after log rotation, so the new file is counted from the start.
+    Replaced,
+}
+
+// Keeps counting a file as it grows. Each poll only reads the bytes appended since the last poll and
+// feeds them to the same counter, so a word or character split across two polls is counted once.
+// Nothing but the file's size and identity are checked, so it works on any file system.
+pub struct Follower {
+    path: PathBuf,
+    options: Options,
+    file: Option<File>,
+    identity: Option<(u64, u64)>,
+    position: u64,
+    counter: Counter,
+    buffer: Vec<u8>,
+}
+
+impl Follower {
+    pub fn new(path: &Path, options: &Options) -> Self {
+        Follower {
+            path: path.to_path_buf(),
+            options: options.clone(),
+            file: None,
+            identity: None,
+            position: 0,
+            counter: Counter::new(options),
+            buffer: vec![0; options.buffer_size.max(1)],
+        }
+    }
+
+    // Returns the counts so far when they have changed since the last poll. A path that has
+    // disappeared, as it does for a moment while a log is rotated, is not an error once the file
+    // has been opened; the counts just stay the same until the file comes back.
+    pub fn poll(&mut self) -> io::Result<Option<(Change, Counts)>> {
+        let metadata = match fs::metadata(&self.path) {
+            Ok(metadata) => metadata,
+            Err(error) if error.kind() == io::ErrorKind::NotFound && self.file.is_some() => return Ok(None),
+            Err(error) => return Err(error),
+        };
+
+        let mut change = Change::Appended;
+        if self.file.is_none() || identity(&metadata) != self.identity {
+            change = if self.file.is_none() { Change::Opened } else { Change::Replaced };
+            self.file = Some(File::open(&self.path)?);
+            self.identity = identity(&metadata);
+            self.restart();
+        } else if metadata.len() < self.position {
+            change = Change::Truncated;
+            self.file.as_mut().unwrap().seek(SeekFrom::Start(0))?;
+            self.restart();
+        }
+
+        let file = self.file.as_mut().unwrap();
+        let mut appended = false;
+        loop {
+            let read_count = file.read(&mut self.buffer)?;
+            if read_count == 0 {
+                break;
+            }
+            self.counter.feed(&self.buffer[0..read_count]);
+            self.position += read_count as u64;
+            appended = true;
+        }
+
+        if change == Change::Appended && !appended {
+            return Ok(None);
+        }
+        Ok(Some((change, self.counter.clone().finish())))
+    }
+
+    fn restart(&mut self) {
+        self.position = 0;
+        self.counter = Counter::new(&self.options);
+    }
+}
+
+// The device and inode numbers, which change when a file is replaced by another with the same name.
+#[cfg(unix)]
+fn identity(metadata: &Metadata) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+    Some((metadata.dev(), metadata.ino()))
+}
+
+#[cfg(not(unix))]
+fn identity(_metadata: &Metadata) -> Option<(u64, u64)> {
+    None
+}
+
+#[cfg(test)]
+mod follower_tests {
+    use super::*;
+    use std::{env, fs::OpenOptions, io::Write};
+
+    fn temp_path(name: &str) -> PathBuf {
+        let path = env::temp_dir().join(format!("ccwc-follow-{}-{}", name, std::process::id()));
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    fn append(path: &Path, contents: &str) {
+        OpenOptions::new().create(true).append(true).open(path).unwrap().write_all(contents.as_bytes()).unwrap();
+    }
+
+    fn summary(update: Option<(Change, Counts)>) -> Option<(Change, u64, u64, u64)> {
+        update.map(|(change, counts)| (change, counts.line_count, counts.word_count, counts.byte_count))
+    }
+
+    #[test]
+    fn poll_counts_appended_bytes_only() {
+        // Arrange
+        let path = temp_path("append");
+        append(&path, "hello wo");
+        let mut follower = Follower::new(&path, &Options::default());
+
+        //Act
+        let opened = summary(follower.poll().unwrap());
+        let unchanged = summary(follower.poll().unwrap());
+        append(&path, "rld\nagain\n");
+        let appended = summary(follower.poll().unwrap());
+
+        //Assert
+        assert_eq!(opened, Some((Change::Opened, 0, 2, 8)));
+        assert_eq!(unchanged, None);
+        assert_eq!(appended, Some((Change::Appended, 2, 3, 18)));
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn poll_starts_again_after_truncation() {
+        // Arrange
+        let path = temp_path("truncate");
+        append(&path, "one two three\n");
+        let mut follower = Follower::new(&path, &Options::default());
+        follower.poll().unwrap();
+
+        //Act
+        OpenOptions::new().write(true).truncate(true).open(&path).unwrap().write_all(b"four\n").unwrap();
+        let truncated = summary(follower.poll().unwrap());
+
+        //Assert
+        assert_eq!(truncated, Some((Change::Truncated, 1, 1, 5)));
+        fs::remove_file(path).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn poll_starts_again_after_rotation() {
+        // Arrange
+        let path = temp_path("rotate");
+        let rotated = temp_path("rotate.1");
+        append(&path, "one two three\n");
+        let mut follower = Follower::new(&path, &Options::default());
+        follower.poll().unwrap();
+
+        //Act
+        fs::rename(&path, &rotated).unwrap();
+        let missing = summary(follower.poll().unwrap());
+        append(&path, "four five six seven\n");
+        let replaced = summary(follower.poll().unwrap());
+
+        //Assert
+        assert_eq!(missing, None);
+        assert_eq!(replaced, Some((Change::Replaced, 1, 4, 20)));
+        fs::remove_file(path).unwrap();
+        fs::remove_file(rotated).unwrap();
+    }
+}
@@ -0,0 +1,5 @@
+// The counting behind ccwc, for use from other programs. Input can be counted from a reader, or
+// fed to a `Counter` a piece at a time as it arrives.
+pub mod wc;
+
+pub use wc::{Counter, Counts, Error, Options};
@@ -1,93 +1,752 @@
+mod baseline;
+mod decompress;
+mod files0;
+mod follow;
+mod output;
+mod walk;
 
-pub mod wc;
-
-use std::io;
+use std::{
+    collections::{BTreeMap, HashSet},
+    fs::File,
+    io::{self, BufRead, BufReader, Write},
+    path::Path,
+    process,
+    sync::Arc,
+    thread,
+    time::Duration,
+};
 
+use baseline::Baseline;
+use ccwc::wc;
 use clap::{Arg, Command, ArgAction};
+use decompress::{Compression, Decompress};
+use follow::{Change, Follower};
+use output::{Column, Format, Grouping, Report, Row, Subtotals, TopWords};
+use walk::{WalkOptions, Walker};
 
 struct Arguments<'a> {
     byte_count: bool,
+    compressed_byte_count: bool,
     char_count: bool,
     line_count: bool,
     word_count: bool,
-    filepath: Option<&'a String>,
+    grapheme_count: bool,
+    token_count: bool,
+    max_line_length: bool,
+    line_stats: bool,
+    code: bool,
+    line_endings: bool,
+    classes: bool,
+    text_stats: bool,
+    check_utf8: bool,
+    format: Format,
+    recursive: bool,
+    walk_options: WalkOptions<'a>,
+    by_extension: bool,
+    language: Option<wc::Language>,
+    top: Option<usize>,
+    save_baseline: Option<&'a String>,
+    compare: Option<&'a String>,
+    max_growth: Option<f64>,
+    threads: usize,
+    decompress: Decompress,
+    follow: bool,
+    interval: Duration,
+    options: wc::Options,
+    files0_from: Option<&'a String>,
+    filepaths: Vec<&'a String>,
+}
+
+// The counts for every input, with the running total and the subtotals for each file extension and language.
+// Word frequencies are only kept in the total, so memory doesn't grow with the number of inputs.
+#[derive(Default)]
+struct Summary {
+    rows: Vec<Row>,
+    total: wc::Counts,
+    by_extension: BTreeMap<String, wc::Counts>,
+    by_language: BTreeMap<wc::Language, wc::Counts>,
+    inputs: usize,
+    failed: bool,
+}
+
+impl Summary {
+    fn record(&mut self, path: &Path, language: Option<wc::Language>, result: Result<wc::Counts, wc::Error>) {
+        self.inputs += 1;
+        match result {
+            Ok(mut counts) => {
+                if !report_utf8_problems(&path.display().to_string(), &counts) {
+                    self.failed = true;
+                }
+                report_mixed_line_endings(&path.display().to_string(), &counts);
+                let word_frequencies = counts.word_frequencies.take();
+                self.total += wc::Counts { word_frequencies, ..wc::Counts::default() };
+                let extension = path.extension().map(|extension| extension.to_string_lossy().into_owned()).unwrap_or_default();
+                *self.by_extension.entry(extension).or_default() += counts.clone();
+                if let Some(language) = language {
+                    *self.by_language.entry(language).or_default() += counts.clone();
+                }
+                self.total += counts.clone();
+                self.rows.push(Row { name: Some(path.display().to_string()), counts });
+            }
+            Err(error) => {
+                eprintln!("ccwc: {}: {}", path.display(), error);
+                self.failed = true;
+            }
+        }
+    }
+}
+
+// Writes the position of each invalid UTF-8 sequence that was kept to stderr. Returns whether the input was valid.
+fn report_utf8_problems(name: &str, counts: &wc::Counts) -> bool {
+    for problem in &counts.utf8_problems {
+        eprintln!("ccwc: {}:{}: invalid UTF-8 at byte {}: {}", name, problem.line, problem.offset, problem.kind);
+    }
+    let unreported = counts.invalid_utf8 - counts.utf8_problems.len() as u64;
+    if unreported > 0 {
+        eprintln!("ccwc: {}: {} more invalid UTF-8 sequences", name, unreported);
+    }
+    counts.invalid_utf8 == 0
+}
+
+// Warns on stderr when the lines of an input don't all end the same way.
+fn report_mixed_line_endings(name: &str, counts: &wc::Counts) {
+    let line_endings = &counts.line_endings;
+    if line_endings.is_mixed() {
+        eprintln!("ccwc: {}: mixed line endings: {} LF, {} CRLF, {} CR", name, line_endings.lf, line_endings.crlf, line_endings.cr);
+    }
 }
 
 fn cli() -> Command {
     Command::new("ccwc")
-        .about("Count characters, words, and lines in a file. Assumes UTF-8 encoding.")
-        .after_help("When an option is specified, wc only reports the information requested by that option.  The order of output always takes the form of line, word, byte, and file name.  The default action is equivalent to specifying the -c, -l and -w options.")
+        .about("Count characters, words, and lines in a file. Assumes UTF-8 encoding unless the file starts with a UTF-16 byte order mark or --encoding is given.")
+        .after_help("When an option is specified, wc only reports the information requested by that option.  The order of output always takes the form of line, word, byte, compressed byte, grapheme, token, line length, code, comment and blank lines, LF, CRLF and CR line endings, character classes, text statistics, invalid UTF-8, and file name.  The default action is equivalent to specifying the -c, -l and -w options.\n\nIf no files are specified, the standard input is used and no file name is displayed.  A file name of '-' also reads the standard input.  When more than one file is given, a total line is written after the last file.")
         .arg(Arg::new("byte_count").short('c').action(ArgAction::SetTrue).help("The number of bytes in each input file is written to the standard output.  This will cancel out any prior usage of the -m option."))
-        .arg(Arg::new("char_count").short('m').action(ArgAction::SetTrue).help("The number of lines in each input file is written to the standard output."))
-        .arg(Arg::new("line_count").short('l').action(ArgAction::SetTrue).help("The number of characters in each input file is written to the standard output.  If the current locale does not support multibyte characters, this is equivalent to the -c option.  This will cancel out any prior usage of the -c option."))
+        .arg(Arg::new("compressed_byte_count").long("bytes-compressed").action(ArgAction::SetTrue).help("The size of each input file before it was decompressed, as stored on disk, is written to the standard output."))
+        .arg(Arg::new("char_count").short('m').action(ArgAction::SetTrue).help("The number of characters in each input file is written to the standard output.  If the current locale does not support multibyte characters, this is equivalent to the -c option.  This will cancel out any prior usage of the -c option."))
+        .arg(Arg::new("line_count").short('l').action(ArgAction::SetTrue).help("The number of lines in each input file is written to the standard output."))
         .arg(Arg::new("word_count").short('w').action(ArgAction::SetTrue).help("The number of words in each input file is written to the standard output."))
-        .arg(Arg::new("filepath"))
+        .arg(Arg::new("grapheme_count").short('g').long("graphemes").action(ArgAction::SetTrue).help("The number of user-perceived characters (extended grapheme clusters) in each input file is written to the standard output.  An emoji sequence, a flag or a letter with combining marks each count as one."))
+        .arg(Arg::new("tokens").long("tokens").value_name("VOCAB").help("The number of tokens in each input file is written to the standard output, as counted with the byte-pair encoding vocabulary in VOCAB.  VOCAB is in the tiktoken format, a token in base64 and its rank on each line, such as cl100k_base.tiktoken.  The input is split into words, numbers, symbols and whitespace before it is encoded, much as GPT tokenizers do, so counts are close to but not always the same as theirs."))
+        .arg(Arg::new("max_line_length").short('L').long("max-line-length").action(ArgAction::SetTrue).help("The length of the longest line in each input file is written to the standard output."))
+        .arg(Arg::new("line_stats").long("line-stats").action(ArgAction::SetTrue).help("The shortest, mean and longest line lengths in each input file are written to the standard output, followed by a histogram of line lengths."))
+        .arg(Arg::new("line_length_unit").long("line-length-unit").value_name("UNIT").default_value("columns").value_parser(["bytes", "chars", "columns"]).help("The unit line lengths are measured in.  Columns are the display width, where tabs stop every 8 columns and wide East Asian characters take two columns."))
+        .arg(Arg::new("code").long("code").action(ArgAction::SetTrue).help("The number of lines of code, comment lines and blank lines in each input file is written to the standard output, followed by a subtotal for each language.  The language is chosen by file extension: Rust, C, C++, Python, shell, JSON and Markdown are recognised.  Files in other languages count no lines."))
+        .arg(Arg::new("language").long("language").value_name("LANGUAGE").requires("code").value_parser(wc::Language::ALL.map(|language| language.id())).help("With --code, read every input as LANGUAGE instead of choosing the language by file extension.  Needed to classify the standard input."))
+        .arg(Arg::new("eol").long("eol").value_name("TERMINATOR").default_value("lf").value_parser(["lf", "crlf", "cr", "any"]).help("What ends a line for the line count, line lengths and --from-line and --to-line.  'lf' is a line feed, with or without a carriage return before it, the same as wc.  'crlf' only counts a carriage return followed by a line feed.  'cr' is a carriage return, as in classic Mac OS files.  'any' counts a CRLF pair, or a line feed or carriage return on its own.  A CRLF pair always ends one line."))
+        .arg(Arg::new("line_endings").long("line-endings").action(ArgAction::SetTrue).help("The number of lines ending with LF, CRLF and CR in each input file is written to the standard output.  A warning is written to the standard error for an input that mixes them."))
+        .arg(Arg::new("classes").long("classes").action(ArgAction::SetTrue).help("The number of letters, digits, punctuation and symbols, whitespace and control characters in each input file is written to the standard output, followed by the number of non-ASCII characters and NUL bytes.  The input is read as UTF-8, and an invalid sequence counts as one non-ASCII punctuation character."))
+        .arg(Arg::new("text_stats").long("text-stats").action(ArgAction::SetTrue).help("The number of sentences, paragraphs and syllables in each input file is written to the standard output, followed by the Flesch Reading Ease score and the Flesch-Kincaid grade level.  Words are separated by whitespace, the same as for -w.  A sentence ends with '.', '!' or '?', except after an abbreviation or an initial, and paragraphs are separated by blank lines.  Syllables are estimated for English."))
+        .arg(Arg::new("top").long("top").value_name("N").value_parser(clap::value_parser!(usize)).help("After the counts, write the N most frequent words across all input files with the number of times each appears.  Words are separated by whitespace, the same as for -w."))
+        .arg(Arg::new("ignore_case").long("ignore-case").action(ArgAction::SetTrue).requires("top").help("With --top, count words in lower case so 'The' and 'the' are the same word."))
+        .arg(Arg::new("stop_words").long("stop-words").value_name("FILE").requires("top").help("With --top, never count the words listed in FILE, one per line."))
+        .arg(Arg::new("max_distinct").long("max-distinct").value_name("N").default_value("100000").value_parser(clap::value_parser!(usize)).help("With --top, the number of distinct words counted exactly.  Beyond this the counts are estimated with a count-min sketch, which uses a fixed amount of memory.  Estimates are never too low."))
+        .arg(Arg::new("encoding").long("encoding").value_name("ENCODING").value_parser(["auto", "utf-8", "utf-16le", "utf-16be", "latin1"]).help("The encoding of the input.  'auto' reads UTF-16 when the input starts with a UTF-16 byte order mark, and UTF-8 otherwise.  'latin1' is ISO-8859-1.  Lines, words and characters are counted in the decoded text, the byte count is always the size of the input. [default: auto]"))
+        .arg(Arg::new("check_utf8").long("check-utf8").action(ArgAction::SetTrue).conflicts_with("encoding").help("The number of invalid UTF-8 sequences in each input file is written to the standard output.  The input's own bytes are checked, even when it starts with a UTF-16 byte order mark, and the other counts are the same as without --check-utf8.  The byte offset, line and kind of the first problems are written to the standard error, and the exit status is non-zero if any input is not valid UTF-8."))
+        .arg(Arg::new("max_problems").long("max-problems").value_name("N").default_value("10").value_parser(clap::value_parser!(usize)).help("With --check-utf8, the number of invalid sequences reported for each input file."))
+        .arg(Arg::new("skip_bytes").long("skip-bytes").value_name("N").default_value("0").value_parser(clap::value_parser!(u64)).conflicts_with("follow").help("Skip the first N bytes of each input file, such as a header, and count the rest.  A regular file is seeked rather than read."))
+        .arg(Arg::new("limit_bytes").long("limit-bytes").value_name("N").value_parser(clap::value_parser!(u64)).conflicts_with("follow").help("Count at most N bytes of each input file, after any skipped with --skip-bytes."))
+        .arg(Arg::new("from_line").long("from-line").value_name("N").value_parser(clap::value_parser!(u64).range(1..)).conflicts_with("follow").help("Only count each input file from line N, numbered from 1.  Lines end as chosen with --eol, and are numbered from the first byte after any skipped with --skip-bytes."))
+        .arg(Arg::new("to_line").long("to-line").value_name("N").value_parser(clap::value_parser!(u64).range(1..)).conflicts_with("follow").help("Only count each input file up to and including line N.  Nothing after line N is read."))
+        .arg(Arg::new("format").long("format").value_name("FORMAT").default_value("table").value_parser(["table", "json", "csv"]).help("How the counts are written.  'table' aligns each column to the widest value.  'json' writes one document with an object per file and the total.  'csv' writes a header row followed by a row per file."))
+        .arg(Arg::new("recursive").short('r').long("recursive").action(ArgAction::SetTrue).help("Count every file below each directory operand.  Files that look binary (contain a NUL byte near the start) are skipped."))
+        .arg(Arg::new("include").long("include").value_name("GLOB").action(ArgAction::Append).requires("recursive").help("When recursing, only count files whose path below the directory matches GLOB.  May be given more than once."))
+        .arg(Arg::new("exclude").long("exclude").value_name("GLOB").action(ArgAction::Append).requires("recursive").help("When recursing, skip files and directories whose path below the directory matches GLOB.  May be given more than once."))
+        .arg(Arg::new("gitignore").long("gitignore").action(ArgAction::SetTrue).requires("recursive").help("When recursing, skip files ignored by .gitignore files."))
+        .arg(Arg::new("save_baseline").long("save-baseline").value_name("FILE").conflicts_with("follow").help("Also write the counts of each input file and the total to FILE as JSON, to compare a later run against with --compare.  Only the reported columns are saved."))
+        .arg(Arg::new("compare").long("compare").value_name("FILE").conflicts_with("follow").help("Instead of the counts, write how they changed since the baseline saved in FILE with --save-baseline.  Each changed file is written with its old and new value and the change as a percentage for each column that changed, then the files that were added and removed, then the total.  Only the columns both runs reported are compared."))
+        .arg(Arg::new("max_growth").long("max-growth").value_name("PERCENT").requires("compare").value_parser(clap::value_parser!(f64)).help("With --compare, exit with a non-zero status if any column of any file, or of the total, grew by more than PERCENT since the baseline.  A column that grew from zero always counts.  Each one is written to the standard error."))
+        .arg(Arg::new("by_extension").long("by-extension").action(ArgAction::SetTrue).help("Write a subtotal for each file extension before the total."))
+        .arg(Arg::new("threads").long("threads").value_name("N").default_value("1").value_parser(clap::value_parser!(usize)).help("Split each input file into byte ranges and count them on up to N threads.  The standard input is always counted on a single thread."))
+        .arg(Arg::new("buffer_size").long("buffer-size").value_name("BYTES").default_value("65536").value_parser(clap::value_parser!(usize)).help("The number of bytes read from the input at a time."))
+        .arg(Arg::new("unicode").long("unicode").value_name("RULES").num_args(0..=1).require_equals(true).default_missing_value("whitespace").value_parser(["whitespace", "uax29"]).help("Decode the input as UTF-8 and use Unicode rules for characters and words.  With 'whitespace' words are separated by any Unicode whitespace character.  With 'uax29' words are found using Unicode word segmentation (UAX #29), which also counts words in scripts that are not written with spaces."))
+        .arg(Arg::new("decompress").long("decompress").value_name("FORMAT").default_value("auto").value_parser(["auto", "none", "gzip", "zstd", "bzip2"]).conflicts_with("follow").help("How compressed inputs are read.  'auto' decompresses an input that starts with the magic bytes of gzip, zstd or bzip2, and counts the rest as they are.  'none' counts every input as it is.  'gzip', 'zstd' or 'bzip2' decompress every input in that format.  The counts are of the decompressed content."))
+        .arg(Arg::new("follow").short('f').long("follow").action(ArgAction::SetTrue).requires("filepath").conflicts_with_all(["recursive", "files0_from"]).help("Keep counting a single file as it grows, writing the counts again whenever they change.  Only the bytes appended since the last check are read.  If the file is truncated, or replaced by a new file with the same name as when a log is rotated, it is counted again from the start."))
+        .arg(Arg::new("interval").long("interval").value_name("SECS").default_value("1").value_parser(parse_interval).help("With --follow, the number of seconds to wait between checks of the file."))
+        .arg(Arg::new("files0_from").long("files0-from").value_name("F").conflicts_with("filepath").help("Read the names of the input files from F instead of the command line.  Names are separated by NUL bytes, as written by 'find -print0'.  If F is '-' the names are read from the standard input."))
+        .arg(Arg::new("filepath").action(ArgAction::Append))
 }
 
 fn main() {
     let matches = cli().get_matches();
-    let args = parse_args(&matches);
+    let mut args = parse_args(&matches);
+
+    if let Some(top) = args.top {
+        match frequency_options(&matches, top) {
+            Ok(options) => args.options.word_frequencies = Some(options),
+            Err(error) => {
+                eprintln!("ccwc: {}", error);
+                process::exit(1);
+            }
+        }
+    }
+
+    if let Some(path) = matches.get_one::<String>("tokens") {
+        match wc::Vocabulary::load(Path::new(path)) {
+            Ok(vocabulary) => args.options.tokens = Some(Arc::new(vocabulary)),
+            Err(error) => {
+                eprintln!("ccwc: {}: {}", path, error);
+                process::exit(1);
+            }
+        }
+    }
+
+    if args.filepaths.is_empty() && args.files0_from.is_none() {
+        match process_operand(Path::new("-"), &options_for(Path::new("-"), &args), &args) {
+            Ok(mut counts) => {
+                let valid = report_utf8_problems("-", &counts);
+                report_mixed_line_endings("-", &counts);
+                let word_frequencies = counts.word_frequencies.take();
+                let total = wc::Counts { word_frequencies, ..counts.clone() };
+                let reported = print_report(&args, &Summary { rows: vec![Row { name: None, counts }], total, ..Summary::default() });
+                if !valid || !reported {
+                    process::exit(1);
+                }
+            }
+            Err(error) => {
+                eprintln!("ccwc: {}", error);
+                process::exit(1);
+            }
+        }
+        return;
+    }
+
+    if args.follow {
+        follow(&args);
+    }
+
+    let walker = match Walker::new(&args.walk_options) {
+        Ok(walker) => walker,
+        Err(error) => {
+            eprintln!("ccwc: {}", error);
+            process::exit(1);
+        }
+    };
+
+    let mut summary = Summary::default();
+    for filepath in &args.filepaths {
+        count_operand(Path::new(filepath.as_str()), &args, &walker, &mut summary);
+    }
+
+    if let Some(list) = args.files0_from {
+        if let Err(error) = count_files0_from(list, &args, &walker, &mut summary) {
+            eprintln!("ccwc: {}: {}", list, error);
+            summary.failed = true;
+        }
+    }
+
+    let reported = print_report(&args, &summary);
+
+    if summary.failed || !reported {
+        process::exit(1);
+    }
+}
+
+// Counts a file, or with -r every file below a directory.
+fn count_operand(path: &Path, args: &Arguments, walker: &Walker, summary: &mut Summary) {
+    if !(args.recursive && path.is_dir()) {
+        let options = options_for(path, args);
+        summary.record(path, options.language, process_operand(path, &options, args));
+        return;
+    }
+
+    for file in walker.files(path) {
+        match file {
+            Ok(file) => match is_countable(&file, args) {
+                Ok(false) => {}
+                Ok(true) => {
+                    let options = options_for(&file, args);
+                    summary.record(&file, options.language, process_operand(&file, &options, args))
+                }
+                Err(error) => summary.record(&file, None, Err(error.into())),
+            },
+            Err(error) => {
+                eprintln!("ccwc: {}", error);
+                summary.failed = true;
+            }
+        }
+    }
+}
+
+fn count_files0_from(list: &str, args: &Arguments, walker: &Walker, summary: &mut Summary) -> io::Result<()> {
+    let reader: Box<dyn BufRead> = if list == "-" {
+        Box::new(io::stdin().lock())
+    } else {
+        Box::new(BufReader::new(File::open(list)?))
+    };
+
+    for name in files0::file_names(reader) {
+        let name = name?;
+        match name.path {
+            None => {
+                eprintln!("ccwc: {}:{}: invalid zero-length file name", list, name.number);
+                summary.failed = true;
+            }
+            Some(path) if list == "-" && path == Path::new("-") => {
+                eprintln!("ccwc: when reading file names from standard input, no file name of '-' allowed");
+                summary.failed = true;
+            }
+            Some(path) => count_operand(&path, args, walker, summary),
+        }
+    }
+
+    Ok(())
+}
+
+// Polls the file forever, writing a row each time the counts change. Only returns by exiting.
+fn follow(args: &Arguments) {
+    let [filepath] = args.filepaths[..] else {
+        eprintln!("ccwc: --follow needs exactly one file");
+        process::exit(1);
+    };
+    if filepath == "-" {
+        eprintln!("ccwc: --follow can't follow the standard input");
+        process::exit(1);
+    }
+
+    let columns = columns(args);
+    let report = Report { format: args.format, columns: &columns, histogram: args.line_stats, top_words: None };
+    let path = Path::new(filepath.as_str());
+    let mut follower = Follower::new(path, &options_for(path, args));
+
+    loop {
+        match follower.poll() {
+            Ok(Some((change, counts))) => {
+                match change {
+                    Change::Truncated => eprintln!("ccwc: {}: file truncated", filepath),
+                    Change::Replaced => eprintln!("ccwc: {}: file replaced", filepath),
+                    Change::Opened | Change::Appended => {}
+                }
+                print!("{}", report.render(&[Row { name: Some(filepath.to_string()), counts }], &[], None));
+                let _ = io::stdout().flush();
+            }
+            Ok(None) => {}
+            Err(error) => {
+                eprintln!("ccwc: {}: {}", filepath, error);
+                process::exit(1);
+            }
+        }
+        thread::sleep(args.interval);
+    }
+}
+
+// Returns false when a baseline can't be saved or loaded, or a column grew by more than --max-growth.
+fn print_report(args: &Arguments, summary: &Summary) -> bool {
+    let columns = columns(args);
+    let baseline = (args.save_baseline.is_some() || args.compare.is_some()).then(|| Baseline::new(&summary.rows, &summary.total, &columns));
+    // The old baseline is loaded before this run is saved, so --compare and --save-baseline can
+    // name the same file.
+    let previous = args.compare.map(|path| (path, Baseline::load(Path::new(path))));
+    let mut ok = true;
+    if let (Some(path), Some(baseline)) = (args.save_baseline, &baseline) {
+        if let Err(error) = baseline.save(Path::new(path)) {
+            eprintln!("ccwc: {}: {}", path, error);
+            ok = false;
+        }
+    }
+    if let (Some((path, previous)), Some(baseline)) = (previous, &baseline) {
+        return print_comparison(args, path, previous, baseline) && ok;
+    }
 
-    let result: Result<wc::Counts, Box<dyn std::error::Error>>;
-    if args.filepath.is_none() {
-        result = wc::process_reader(io::stdin());
+    let top_words = summary.total.word_frequencies.as_ref().map(|frequencies| TopWords {
+        words: frequencies.top(args.top.unwrap_or_default()),
+        approximate: frequencies.is_approximate(),
+    });
+    if top_words.as_ref().is_some_and(|top_words| top_words.approximate) && args.format != Format::Json {
+        eprintln!("ccwc: too many distinct words to count exactly, word counts are estimates");
     }
-    else {
-        let str = args.filepath.unwrap();
-        result = wc::process_file(str.clone());    
+    let report = Report { format: args.format, columns: &columns, histogram: args.line_stats, top_words: top_words.as_ref() };
+
+    let mut subtotals = Vec::new();
+    if args.by_extension {
+        let rows = summary
+            .by_extension
+            .iter()
+            .map(|(extension, counts)| Row { name: Some(extension.clone()), counts: counts.clone() })
+            .collect();
+        subtotals.push(Subtotals { grouping: Grouping::Extension, rows });
+    }
+    if args.code && summary.inputs > 1 {
+        let rows = summary
+            .by_language
+            .iter()
+            .map(|(language, counts)| Row { name: Some(language.name().to_string()), counts: counts.clone() })
+            .collect();
+        subtotals.push(Subtotals { grouping: Grouping::Language, rows });
     }
-    
-    if result.is_err() {
-        println!("Error: {}", result.err().unwrap());
+    let total = (summary.inputs > 1).then_some(&summary.total);
+
+    print!("{}", report.render(&summary.rows, &subtotals, total));
+    ok
+}
+
+// Writes what changed since the baseline loaded from `path`, and with --max-growth each column
+// that grew too much to stderr.
+fn print_comparison(args: &Arguments, path: &str, baseline: io::Result<Baseline>, current: &Baseline) -> bool {
+    let baseline = match baseline {
+        Ok(baseline) => baseline,
+        Err(error) => {
+            eprintln!("ccwc: {}: {}", path, error);
+            return false;
+        }
+    };
+    let comparison = current.compare(&baseline);
+    print!("{}", comparison.render(args.format));
+
+    let Some(max_growth) = args.max_growth else {
+        return true;
+    };
+    let growth = comparison.growth_above(max_growth);
+    for (name, metric) in &growth {
+        match metric.growth_percent() {
+            Some(percent) => eprintln!("ccwc: {}: {} grew by {:.1}%, more than {}%", name, metric.label, percent, max_growth),
+            None => eprintln!("ccwc: {}: {} grew from 0 to {}", name, metric.label, metric.current),
+        }
     }
-    else {
-        print_counts(args, result.unwrap());
+    growth.is_empty()
+}
+
+// With --code the language, and so the comment syntax, depends on the file.
+fn options_for(path: &Path, args: &Arguments) -> wc::Options {
+    let language = if args.code { args.language.or_else(|| wc::Language::from_path(path)) } else { None };
+    wc::Options { language, ..args.options.clone() }
+}
+
+// Files that look binary are skipped when recursing, unless they are compressed.
+fn is_countable(path: &Path, args: &Arguments) -> io::Result<bool> {
+    Ok(!walk::is_binary(path)? || decompress::detect(path, args.decompress)?.is_some())
+}
+
+// A compressed input is decompressed as it is read, so it is always counted on a single thread.
+fn process_operand(filepath: &Path, options: &wc::Options, args: &Arguments) -> Result<wc::Counts, wc::Error> {
+    if filepath == Path::new("-") {
+        let input = decompress::open(io::stdin(), args.decompress)?;
+        let mut counts = wc::process_reader_with_options(input.reader, options)?;
+        counts.compressed_bytes = input.raw_bytes.get();
+        return Ok(counts);
     }
+
+    let file = File::open(filepath)?;
+    let metadata = file.metadata()?;
+    let input = decompress::open(file, args.decompress)?;
+    // Only a regular file can be opened again to read the magic bytes a second time. A pipe, such
+    // as /dev/stdin or a process substitution, is counted from the reader they were put back into.
+    let mut counts = if input.compression.is_some() || !metadata.is_file() {
+        wc::process_reader_with_options(input.reader, options)?
+    } else if args.threads > 1 {
+        wc::process_file_parallel(filepath, args.threads, options)?
+    } else {
+        wc::process_file_with_options(filepath, options)?
+    };
+    counts.compressed_bytes = if metadata.is_file() { metadata.len() } else { input.raw_bytes.get() };
+    Ok(counts)
 }
 
-fn parse_args(matches: &clap::ArgMatches) -> Arguments {
+fn frequency_options(matches: &clap::ArgMatches, top: usize) -> Result<wc::FrequencyOptions, String> {
+    let fold_case = matches.get_flag("ignore_case");
+    let fold = |word: &str| if fold_case { word.to_lowercase() } else { word.to_string() };
+
+    let stop_words = match matches.get_one::<String>("stop_words") {
+        Some(path) => {
+            let contents = std::fs::read(path).map_err(|error| format!("{}: {}", path, error))?;
+            String::from_utf8_lossy(&contents).split_whitespace().map(fold).collect()
+        }
+        None => HashSet::new(),
+    };
+
+    Ok(wc::FrequencyOptions {
+        top,
+        fold_case,
+        stop_words: Arc::new(stop_words),
+        max_distinct: *matches.get_one::<usize>("max_distinct").unwrap(),
+    })
+}
+
+fn parse_interval(value: &str) -> Result<Duration, String> {
+    let seconds: f64 = value.parse().map_err(|_| format!("'{}' is not a number of seconds", value))?;
+    Duration::try_from_secs_f64(seconds).map_err(|_| format!("'{}' is not a number of seconds", value))
+}
+
+fn parse_args(matches: &clap::ArgMatches) -> Arguments<'_> {
     let get_byte_count: bool = matches.get_flag("byte_count");
+    let get_compressed_byte_count: bool = matches.get_flag("compressed_byte_count");
     let get_char_count: bool = matches.get_flag("char_count");
     let get_line_count: bool = matches.get_flag("line_count");
     let get_word_count: bool = matches.get_flag("word_count");
-    let filepath = matches.try_get_one::<String>("filepath").unwrap_or_default();
-    
-    if !get_byte_count && !get_char_count && !get_line_count && !get_word_count {
+    let get_grapheme_count: bool = matches.get_flag("grapheme_count");
+    let get_token_count: bool = matches.contains_id("tokens");
+    let get_max_line_length: bool = matches.get_flag("max_line_length");
+    let get_line_stats: bool = matches.get_flag("line_stats");
+    let get_code: bool = matches.get_flag("code");
+    let get_line_endings: bool = matches.get_flag("line_endings");
+    let get_classes: bool = matches.get_flag("classes");
+    let get_text_stats: bool = matches.get_flag("text_stats");
+    let get_check_utf8: bool = matches.get_flag("check_utf8");
+    let format = match matches.get_one::<String>("format").unwrap().as_str() {
+        "json" => Format::Json,
+        "csv" => Format::Csv,
+        _ => Format::Table,
+    };
+    let threads: usize = *matches.get_one::<usize>("threads").unwrap();
+    let decompress = match matches.get_one::<String>("decompress").unwrap().as_str() {
+        "none" => Decompress::Never,
+        "gzip" => Decompress::Always(Compression::Gzip),
+        "zstd" => Decompress::Always(Compression::Zstd),
+        "bzip2" => Decompress::Always(Compression::Bzip2),
+        _ => Decompress::Auto,
+    };
+    let follow = matches.get_flag("follow");
+    let interval: Duration = *matches.get_one::<Duration>("interval").unwrap();
+    let text_mode = match matches.get_one::<String>("unicode").map(|rules| rules.as_str()) {
+        Some("whitespace") => wc::TextMode::Unicode,
+        Some("uax29") => wc::TextMode::Uax29,
+        _ => wc::TextMode::Ascii,
+    };
+    let line_length_unit = match matches.get_one::<String>("line_length_unit").unwrap().as_str() {
+        "bytes" => wc::LineLengthUnit::Bytes,
+        "chars" => wc::LineLengthUnit::Chars,
+        _ => wc::LineLengthUnit::Columns,
+    };
+    let line_terminator = match matches.get_one::<String>("eol").unwrap().as_str() {
+        "crlf" => wc::LineTerminator::Crlf,
+        "cr" => wc::LineTerminator::Cr,
+        "any" => wc::LineTerminator::Any,
+        _ => wc::LineTerminator::Lf,
+    };
+    let encoding = match matches.get_one::<String>("encoding").map(|encoding| encoding.as_str()) {
+        Some("utf-8") => wc::Encoding::Utf8,
+        Some("utf-16le") => wc::Encoding::Utf16Le,
+        Some("utf-16be") => wc::Encoding::Utf16Be,
+        Some("latin1") => wc::Encoding::Latin1,
+        _ => wc::Encoding::Auto,
+    };
+    let options = wc::Options {
+        buffer_size: *matches.get_one::<usize>("buffer_size").unwrap(),
+        text_mode,
+        graphemes: get_grapheme_count,
+        tokens: None,
+        line_lengths: (get_max_line_length || get_line_stats).then_some(line_length_unit),
+        line_terminator,
+        line_endings: get_line_endings,
+        encoding,
+        language: None,
+        classes: get_classes,
+        text_stats: get_text_stats,
+        word_frequencies: None,
+        validate_utf8: get_check_utf8.then(|| *matches.get_one::<usize>("max_problems").unwrap()),
+        skip_bytes: *matches.get_one::<u64>("skip_bytes").unwrap(),
+        limit_bytes: matches.get_one::<u64>("limit_bytes").copied(),
+        from_line: matches.get_one::<u64>("from_line").copied(),
+        to_line: matches.get_one::<u64>("to_line").copied(),
+    };
+    let walk_options = WalkOptions {
+        include: matches.get_many::<String>("include").map(|values| values.collect()).unwrap_or_default(),
+        exclude: matches.get_many::<String>("exclude").map(|values| values.collect()).unwrap_or_default(),
+        gitignore: matches.get_flag("gitignore"),
+    };
+    let recursive = matches.get_flag("recursive");
+    let by_extension = matches.get_flag("by_extension");
+    let language = matches.get_one::<String>("language").and_then(|name| wc::Language::from_name(name));
+    let top = matches.get_one::<usize>("top").copied();
+    let save_baseline = matches.get_one::<String>("save_baseline");
+    let compare = matches.get_one::<String>("compare");
+    let max_growth = matches.get_one::<f64>("max_growth").copied();
+    let files0_from = matches.get_one::<String>("files0_from");
+    let filepaths = matches
+        .get_many::<String>("filepath")
+        .map(|values| values.collect())
+        .unwrap_or_default();
+
+    if !get_byte_count && !get_compressed_byte_count && !get_char_count && !get_line_count && !get_word_count && !get_grapheme_count && !get_token_count && !get_max_line_length && !get_line_stats && !get_code && !get_line_endings && !get_classes && !get_text_stats && !get_check_utf8 {
         return Arguments {
             byte_count: true,
+            compressed_byte_count: false,
             char_count: true,
             line_count: true,
             word_count: true,
-            filepath: filepath.clone(),
+            grapheme_count: false,
+            token_count: false,
+            max_line_length: false,
+            line_stats: false,
+            code: false,
+            line_endings: false,
+            classes: false,
+            text_stats: false,
+            check_utf8: false,
+            format,
+            recursive,
+            walk_options,
+            by_extension,
+            language,
+            top,
+            save_baseline,
+            compare,
+            max_growth,
+            threads,
+            decompress,
+            follow,
+            interval,
+            options,
+            files0_from,
+            filepaths,
         }
     }
 
-    return Arguments { 
+    Arguments {
         byte_count: get_byte_count,
+        compressed_byte_count: get_compressed_byte_count,
         char_count: get_char_count,
         line_count: get_line_count,
-        word_count: get_line_count,
-        filepath: filepath.clone(),
+        word_count: get_word_count,
+        grapheme_count: get_grapheme_count,
+        token_count: get_token_count,
+        max_line_length: get_max_line_length,
+        line_stats: get_line_stats,
+        code: get_code,
+        line_endings: get_line_endings,
+        classes: get_classes,
+        text_stats: get_text_stats,
+        check_utf8: get_check_utf8,
+        format,
+        recursive,
+        walk_options,
+        by_extension,
+        language,
+        top,
+        save_baseline,
+        compare,
+        max_growth,
+        threads,
+        decompress,
+        follow,
+        interval,
+        options,
+        files0_from,
+        filepaths,
     }
 }
 
-fn print_counts(args: Arguments, counts: wc::Counts) {
+fn columns(args: &Arguments) -> Vec<Column> {
+    let mut columns = Vec::new();
+
     if args.line_count {
-        print!("{}  ", counts.line_count);
+        columns.push(Column::Lines);
     }
 
     if args.word_count {
-        print!("{}  ", counts.word_count);
-    } 
-    
+        columns.push(Column::Words);
+    }
+
     if args.byte_count {
-        print!("{}  ", counts.byte_count);
+        columns.push(Column::Bytes);
     } else if args.char_count {
-        print!("{}  ", counts.char_count);
+        columns.push(Column::Chars);
     }
-    
-    if args.filepath.is_some() {
-        print!("{}\n", args.filepath.clone().unwrap());
-        return;
+
+    if args.compressed_byte_count {
+        columns.push(Column::CompressedBytes);
+    }
+
+    if args.grapheme_count {
+        columns.push(Column::Graphemes);
+    }
+
+    if args.token_count {
+        columns.push(Column::Tokens);
+    }
+
+    if args.line_stats {
+        columns.extend([Column::MinLineLength, Column::MeanLineLength, Column::MaxLineLength]);
+    } else if args.max_line_length {
+        columns.push(Column::MaxLineLength);
+    }
+
+    if args.code {
+        columns.extend([Column::CodeLines, Column::CommentLines, Column::BlankLines]);
+    }
+
+    if args.line_endings {
+        columns.extend([Column::LfLines, Column::CrlfLines, Column::CrLines]);
+    }
+
+    if args.classes {
+        columns.extend([Column::Letters, Column::Digits, Column::Punctuation, Column::Whitespace, Column::Control, Column::NonAscii, Column::Nul]);
+    }
+
+    if args.text_stats {
+        columns.extend([Column::Sentences, Column::Paragraphs, Column::Syllables, Column::ReadingEase, Column::GradeLevel]);
+    }
+
+    if args.check_utf8 {
+        columns.push(Column::InvalidUtf8);
+    }
+
+    columns
+}
+
+#[cfg(test)]
+mod main_tests {
+    use super::*;
+    use std::os::fd::AsRawFd;
+
+    #[test]
+    fn a_pipe_operand_is_counted_from_its_first_byte() {
+        // Arrange
+        let (reader, mut writer) = io::pipe().unwrap();
+        writer.write_all(b"hello world\nfoo\n").unwrap();
+        drop(writer);
+        let path = format!("/dev/fd/{}", reader.as_raw_fd());
+        let matches = cli().get_matches_from(["ccwc", path.as_str()]);
+        let args = parse_args(&matches);
+
+        //Act
+        let counts = process_operand(Path::new(&path), &args.options, &args).unwrap();
+
+        //Assert
+        assert_eq!((counts.line_count, counts.word_count, counts.byte_count), (2, 3, 16));
+    }
+
+    #[test]
+    fn follow_needs_a_file_operand() {
+        //Act
+        let error = cli().try_get_matches_from(["ccwc", "-f"]).unwrap_err();
+
+        //Assert
+        assert_eq!(error.kind(), clap::error::ErrorKind::MissingRequiredArgument);
+    }
+
+    #[test]
+    fn walk_options_need_recursion() {
+        //Act
+        let include = cli().try_get_matches_from(["ccwc", "--include", "[", "a.txt"]).unwrap_err();
+        let exclude = cli().try_get_matches_from(["ccwc", "--exclude", "*.log", "a.txt"]).unwrap_err();
+        let recursive = cli().try_get_matches_from(["ccwc", "-r", "--include", "*.rs", "src"]);
+
+        //Assert
+        assert_eq!(include.kind(), clap::error::ErrorKind::MissingRequiredArgument);
+        assert_eq!(exclude.kind(), clap::error::ErrorKind::MissingRequiredArgument);
+        assert!(recursive.is_ok());
+    }
+
+    #[test]
+    fn compare_and_save_baseline_can_name_the_same_file() {
+        // Arrange
+        let path = std::env::temp_dir().join(format!("ccwc-ratchet-{}.json", process::id()));
+        let path = path.to_str().unwrap();
+        let matches = cli().get_matches_from(["ccwc", "-l", "--compare", path, "--save-baseline", path, "--max-growth", "10", "a.txt"]);
+        let args = parse_args(&matches);
+        let summary = |lines| {
+            let counts = wc::Counts { line_count: lines, ..wc::Counts::default() };
+            Summary { rows: vec![Row { name: Some("a.txt".to_string()), counts: counts.clone() }], total: counts, inputs: 1, ..Summary::default() }
+        };
+        let (old, new) = (summary(1), summary(3));
+        Baseline::new(&old.rows, &old.total, &columns(&args)).save(Path::new(path)).unwrap();
+
+        //Act
+        let reported = print_report(&args, &new);
+
+        //Assert
+        assert!(!reported);
+        assert_eq!(Baseline::load(Path::new(path)).unwrap(), Baseline::new(&new.rows, &new.total, &columns(&args)));
+        std::fs::remove_file(path).unwrap();
     }
 }
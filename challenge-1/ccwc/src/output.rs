@@ -0,0 +1,435 @@
+use serde_json::{json, Map, Value};
+
+use ccwc::wc::{Counts, HISTOGRAM_BUCKET_WIDTH};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Format {
+    Table,
+    Json,
+    Csv,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Column {
+    Lines,
+    Words,
+    Bytes,
+    CompressedBytes,
+    Chars,
+    Graphemes,
+    Tokens,
+    MaxLineLength,
+    MinLineLength,
+    MeanLineLength,
+    CodeLines,
+    CommentLines,
+    BlankLines,
+    LfLines,
+    CrlfLines,
+    CrLines,
+    Letters,
+    Digits,
+    Punctuation,
+    Whitespace,
+    Control,
+    NonAscii,
+    Nul,
+    Sentences,
+    Paragraphs,
+    Syllables,
+    ReadingEase,
+    GradeLevel,
+    InvalidUtf8,
+}
+
+impl Column {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Column::Lines => "lines",
+            Column::Words => "words",
+            Column::Bytes => "bytes",
+            Column::CompressedBytes => "compressed_bytes",
+            Column::Chars => "chars",
+            Column::Graphemes => "graphemes",
+            Column::Tokens => "tokens",
+            Column::MaxLineLength => "max_line_length",
+            Column::MinLineLength => "min_line_length",
+            Column::MeanLineLength => "mean_line_length",
+            Column::CodeLines => "code_lines",
+            Column::CommentLines => "comment_lines",
+            Column::BlankLines => "blank_lines",
+            Column::LfLines => "lf_lines",
+            Column::CrlfLines => "crlf_lines",
+            Column::CrLines => "cr_lines",
+            Column::Letters => "letters",
+            Column::Digits => "digits",
+            Column::Punctuation => "punctuation",
+            Column::Whitespace => "whitespace",
+            Column::Control => "control",
+            Column::NonAscii => "non_ascii",
+            Column::Nul => "nul",
+            Column::Sentences => "sentences",
+            Column::Paragraphs => "paragraphs",
+            Column::Syllables => "syllables",
+            Column::ReadingEase => "reading_ease",
+            Column::GradeLevel => "grade_level",
+            Column::InvalidUtf8 => "invalid_utf8",
+        }
+    }
+
+    pub fn value(&self, counts: &Counts) -> Value {
+        match self {
+            Column::Lines => json!(counts.line_count),
+            Column::Words => json!(counts.word_count),
+            Column::Bytes => json!(counts.byte_count),
+            Column::CompressedBytes => json!(counts.compressed_bytes),
+            Column::Chars => json!(counts.char_count),
+            Column::Graphemes => json!(counts.graphemes),
+            Column::Tokens => json!(counts.token_count),
+            Column::MaxLineLength => json!(counts.line_lengths.max),
+            Column::MinLineLength => json!(counts.line_lengths.min),
+            Column::MeanLineLength => json!((counts.line_lengths.mean() * 10.0).round() / 10.0),
+            Column::CodeLines => json!(counts.code_lines.code),
+            Column::CommentLines => json!(counts.code_lines.comment),
+            Column::BlankLines => json!(counts.code_lines.blank),
+            Column::LfLines => json!(counts.line_endings.lf),
+            Column::CrlfLines => json!(counts.line_endings.crlf),
+            Column::CrLines => json!(counts.line_endings.cr),
+            Column::Letters => json!(counts.classes.letters),
+            Column::Digits => json!(counts.classes.digits),
+            Column::Punctuation => json!(counts.classes.punctuation),
+            Column::Whitespace => json!(counts.classes.whitespace),
+            Column::Control => json!(counts.classes.control),
+            Column::NonAscii => json!(counts.classes.non_ascii),
+            Column::Nul => json!(counts.classes.nul),
+            Column::Sentences => json!(counts.text_stats.sentences),
+            Column::Paragraphs => json!(counts.text_stats.paragraphs),
+            Column::Syllables => json!(counts.text_stats.syllables),
+            Column::ReadingEase => json!((counts.text_stats.reading_ease() * 10.0).round() / 10.0),
+            Column::GradeLevel => json!((counts.text_stats.grade_level() * 10.0).round() / 10.0),
+            Column::InvalidUtf8 => json!(counts.invalid_utf8),
+        }
+    }
+
+    fn text(&self, counts: &Counts) -> String {
+        match self {
+            Column::MeanLineLength => format!("{:.1}", counts.line_lengths.mean()),
+            Column::ReadingEase => format!("{:.1}", counts.text_stats.reading_ease()),
+            Column::GradeLevel => format!("{:.1}", counts.text_stats.grade_level()),
+            _ => self.value(counts).to_string(),
+        }
+    }
+}
+
+// A line of output. `name` is None when the standard input was counted without a file name.
+pub struct Row {
+    pub name: Option<String>,
+    pub counts: Counts,
+}
+
+// What the files in a set of subtotals have in common.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Grouping {
+    Extension,
+    Language,
+}
+
+impl Grouping {
+    fn key(&self) -> &'static str {
+        match self {
+            Grouping::Extension => "extensions",
+            Grouping::Language => "languages",
+        }
+    }
+
+    fn row_name(&self, name: Option<&str>) -> String {
+        match (self, name) {
+            (Grouping::Extension, Some(extension)) if !extension.is_empty() => format!("total *.{}", extension),
+            (Grouping::Extension, _) => "total (no extension)".to_string(),
+            (Grouping::Language, name) => format!("total {}", name.unwrap_or_default()),
+        }
+    }
+}
+
+// A row for each extension or language, named by the extension or language.
+pub struct Subtotals {
+    pub grouping: Grouping,
+    pub rows: Vec<Row>,
+}
+
+// The most frequent words and their counts, written after the counts.
+pub struct TopWords {
+    pub words: Vec<(String, u64)>,
+    // The counts are estimates, which are never too low.
+    pub approximate: bool,
+}
+
+pub struct Report<'a> {
+    pub format: Format,
+    pub columns: &'a [Column],
+    pub histogram: bool,
+    pub top_words: Option<&'a TopWords>,
+}
+
+impl Report<'_> {
+    // `subtotals` are written between the files and the total.
+    pub fn render(&self, rows: &[Row], subtotals: &[Subtotals], total: Option<&Counts>) -> String {
+        match self.format {
+            Format::Table => self.table(rows, subtotals, total),
+            Format::Json => self.json(rows, subtotals, total),
+            Format::Csv => self.csv(rows, subtotals, total),
+        }
+    }
+
+    fn lines<'a>(rows: &'a [Row], subtotals: &'a [Subtotals], total: Option<&'a Counts>) -> Vec<(Option<String>, &'a Counts)> {
+        let files = rows.iter().map(|row| (row.name.clone(), &row.counts));
+        let subtotal_rows = subtotals
+            .iter()
+            .flat_map(|group| group.rows.iter().map(|row| (Some(group.grouping.row_name(row.name.as_deref())), &row.counts)));
+        let total_row = total.map(|counts| (Some("total".to_string()), counts));
+        files.chain(subtotal_rows).chain(total_row).collect()
+    }
+
+    // Every value is right aligned to the width of the widest value, the same as GNU wc.
+    fn table(&self, rows: &[Row], subtotals: &[Subtotals], total: Option<&Counts>) -> String {
+        let lines = Self::lines(rows, subtotals, total);
+
+        let width = lines
+            .iter()
+            .flat_map(|(_, counts)| self.columns.iter().map(|column| column.text(counts).len()))
+            .max()
+            .unwrap_or(0);
+
+        let mut output = String::new();
+        for (name, counts) in lines {
+            let mut fields: Vec<String> = self
+                .columns
+                .iter()
+                .map(|column| format!("{:>width$}", column.text(counts)))
+                .collect();
+            if let Some(name) = name {
+                fields.push(name);
+            }
+            output.push_str(&fields.join(" "));
+            output.push('\n');
+
+            if self.histogram {
+                for (bucket, count) in &counts.line_lengths.histogram {
+                    output.push_str(&format!("    {}-{}  {}\n", bucket, bucket + HISTOGRAM_BUCKET_WIDTH - 1, count));
+                }
+            }
+        }
+
+        // The words follow a blank line, each count right aligned like the counts above.
+        if let Some(top_words) = self.top_words {
+            let width = top_words.words.iter().map(|(_, count)| count.to_string().len()).max().unwrap_or(0);
+            output.push('\n');
+            for (word, count) in &top_words.words {
+                output.push_str(&format!("{:>width$} {}\n", count, word));
+            }
+        }
+        output
+    }
+
+    // A single document, `{"files": [...], "extensions": {...}, "languages": {...}, "total": {...}}`.
+    fn json(&self, rows: &[Row], subtotals: &[Subtotals], total: Option<&Counts>) -> String {
+        let files: Vec<Value> = rows.iter().map(|row| self.json_object(row.name.as_deref(), &row.counts)).collect();
+
+        let mut document = Map::new();
+        document.insert("files".to_string(), Value::Array(files));
+        for group in subtotals.iter().filter(|group| !group.rows.is_empty()) {
+            let values: Map<String, Value> = group
+                .rows
+                .iter()
+                .map(|row| (row.name.clone().unwrap_or_default(), self.json_object(None, &row.counts)))
+                .collect();
+            document.insert(group.grouping.key().to_string(), Value::Object(values));
+        }
+        if let Some(total) = total {
+            document.insert("total".to_string(), self.json_object(None, total));
+        }
+        if let Some(top_words) = self.top_words {
+            let words: Vec<Value> = top_words.words.iter().map(|(word, count)| json!({ "word": word, "count": count })).collect();
+            document.insert("top_words".to_string(), json!({ "approximate": top_words.approximate, "words": words }));
+        }
+
+        let mut output = serde_json::to_string_pretty(&document).unwrap();
+        output.push('\n');
+        output
+    }
+
+    fn json_object(&self, name: Option<&str>, counts: &Counts) -> Value {
+        let mut object = Map::new();
+        if let Some(name) = name {
+            object.insert("file".to_string(), json!(name));
+        }
+        for column in self.columns {
+            object.insert(column.label().to_string(), column.value(counts));
+        }
+        if self.histogram {
+            let buckets: Vec<Value> = counts
+                .line_lengths
+                .histogram
+                .iter()
+                .map(|(bucket, count)| json!({ "min": bucket, "max": bucket + HISTOGRAM_BUCKET_WIDTH - 1, "lines": count }))
+                .collect();
+            object.insert("line_length_histogram".to_string(), Value::Array(buckets));
+        }
+        Value::Object(object)
+    }
+
+    // A header row, then one row per file, subtotal and total. The histogram is left out. The top
+    // words follow as a second table, after a blank line.
+    fn csv(&self, rows: &[Row], subtotals: &[Subtotals], total: Option<&Counts>) -> String {
+        let mut header: Vec<&str> = self.columns.iter().map(|column| column.label()).collect();
+        header.push("file");
+
+        let lines = Self::lines(rows, subtotals, total);
+
+        let mut output = header.join(",");
+        output.push('\n');
+        for (name, counts) in lines {
+            let mut fields: Vec<String> = self.columns.iter().map(|column| column.text(counts)).collect();
+            fields.push(csv_field(name.as_deref().unwrap_or("")));
+            output.push_str(&fields.join(","));
+            output.push('\n');
+        }
+
+        if let Some(top_words) = self.top_words {
+            output.push_str("\ncount,word\n");
+            for (word, count) in &top_words.words {
+                output.push_str(&format!("{},{}\n", count, csv_field(word)));
+            }
+        }
+        output
+    }
+}
+
+pub fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        return format!("\"{}\"", field.replace('"', "\"\""));
+    }
+    field.to_string()
+}
+
+#[cfg(test)]
+mod report_tests {
+    use super::*;
+    use ccwc::wc::CodeLines;
+
+    fn rows() -> Vec<Row> {
+        vec![
+            Row { name: Some("a.txt".to_string()), counts: Counts { line_count: 3, word_count: 12, byte_count: 70, ..Counts::default() } },
+            Row { name: Some("b, \"c\".txt".to_string()), counts: Counts { line_count: 1020, word_count: 4, byte_count: 9, ..Counts::default() } },
+        ]
+    }
+
+    fn total() -> Counts {
+        Counts { line_count: 1023, word_count: 16, byte_count: 79, ..Counts::default() }
+    }
+
+    const COLUMNS: [Column; 3] = [Column::Lines, Column::Words, Column::Bytes];
+
+    #[test]
+    fn table_aligns_columns_to_the_widest_value() {
+        // Arrange
+        let report = Report { format: Format::Table, columns: &COLUMNS, histogram: false, top_words: None };
+
+        //Act
+        let output = report.render(&rows(), &[], Some(&total()));
+
+        //Assert
+        assert_eq!(output, "   3   12   70 a.txt\n1020    4    9 b, \"c\".txt\n1023   16   79 total\n");
+    }
+
+    #[test]
+    fn table_without_file_name_ends_with_new_line() {
+        // Arrange
+        let report = Report { format: Format::Table, columns: &COLUMNS, histogram: false, top_words: None };
+        let rows = vec![Row { name: None, counts: total() }];
+
+        //Act
+        let output = report.render(&rows, &[], None);
+
+        //Assert
+        assert_eq!(output, "1023   16   79\n");
+    }
+
+    #[test]
+    fn csv_has_header_and_quotes_file_names() {
+        // Arrange
+        let report = Report { format: Format::Csv, columns: &COLUMNS, histogram: false, top_words: None };
+
+        //Act
+        let output = report.render(&rows(), &[], Some(&total()));
+
+        //Assert
+        assert_eq!(output, "lines,words,bytes,file\n3,12,70,a.txt\n1020,4,9,\"b, \"\"c\"\".txt\"\n1023,16,79,total\n");
+    }
+
+    #[test]
+    fn json_labels_every_value() {
+        // Arrange
+        let report = Report { format: Format::Json, columns: &COLUMNS, histogram: false, top_words: None };
+
+        //Act
+        let output = report.render(&rows(), &[], Some(&total()));
+        let document: Value = serde_json::from_str(&output).unwrap();
+
+        //Assert
+        assert_eq!(document["files"][0], json!({ "file": "a.txt", "lines": 3, "words": 12, "bytes": 70 }));
+        assert_eq!(document["files"][1]["file"], json!("b, \"c\".txt"));
+        assert_eq!(document["total"], json!({ "lines": 1023, "words": 16, "bytes": 79 }));
+    }
+
+    #[test]
+    fn extension_subtotals_come_before_the_total() {
+        // Arrange
+        let report = Report { format: Format::Table, columns: &COLUMNS, histogram: false, top_words: None };
+        let extensions = vec![
+            Row { name: Some("txt".to_string()), counts: Counts { line_count: 1023, word_count: 16, byte_count: 79, ..Counts::default() } },
+            Row { name: Some(String::new()), counts: Counts::default() },
+        ];
+        let subtotals = [Subtotals { grouping: Grouping::Extension, rows: extensions }];
+
+        //Act
+        let output = report.render(&rows(), &subtotals, Some(&total()));
+
+        //Assert
+        assert!(output.ends_with("1023   16   79 total *.txt\n   0    0    0 total (no extension)\n1023   16   79 total\n"));
+    }
+
+    #[test]
+    fn top_words_follow_the_counts() {
+        // Arrange
+        let top_words = TopWords { words: vec![("the".to_string(), 1200), ("a,b".to_string(), 35)], approximate: false };
+        let table = Report { format: Format::Table, columns: &COLUMNS, histogram: false, top_words: Some(&top_words) };
+        let csv = Report { format: Format::Csv, columns: &COLUMNS, histogram: false, top_words: Some(&top_words) };
+        let rows = vec![Row { name: None, counts: total() }];
+
+        //Act
+        let table_output = table.render(&rows, &[], None);
+        let csv_output = csv.render(&rows, &[], None);
+
+        //Assert
+        assert_eq!(table_output, "1023   16   79\n\n1200 the\n  35 a,b\n");
+        assert!(csv_output.ends_with("\ncount,word\n1200,the\n35,\"a,b\"\n"));
+    }
+
+    #[test]
+    fn json_groups_subtotals_by_language() {
+        // Arrange
+        let columns = [Column::CodeLines, Column::CommentLines, Column::BlankLines];
+        let report = Report { format: Format::Json, columns: &columns, histogram: false, top_words: None };
+        let code_lines = CodeLines { code: 10, comment: 2, blank: 3 };
+        let languages = vec![Row { name: Some("Rust".to_string()), counts: Counts { code_lines, ..Counts::default() } }];
+        let subtotals = [Subtotals { grouping: Grouping::Extension, rows: vec![] }, Subtotals { grouping: Grouping::Language, rows: languages }];
+
+        //Act
+        let output = report.render(&[], &subtotals, None);
+        let document: Value = serde_json::from_str(&output).unwrap();
+
+        //Assert
+        assert_eq!(document, json!({ "files": [], "languages": { "Rust": { "code_lines": 10, "comment_lines": 2, "blank_lines": 3 } } }));
+    }
+}
@@ -0,0 +1,172 @@
+use std::{
+    fs::File,
+    io::{self, Read},
+    path::{Path, PathBuf},
+};
+
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use ignore::WalkBuilder;
+
+// Only the start of a file is checked for NUL bytes, the same heuristic used by git and grep.
+const BINARY_CHECK_SIZE: usize = 8 * 1024;
+
+pub struct WalkOptions<'a> {
+    pub include: Vec<&'a String>,
+    pub exclude: Vec<&'a String>,
+    pub gitignore: bool,
+}
+
+// Finds the files to count below a directory. Globs are matched against both the path relative
+// to the directory and the file name, so `*.rs` matches Rust files at any depth, `src/**` matches
+// everything in the top level `src` directory, and `target` matches a `target` directory anywhere.
+pub struct Walker {
+    include: Option<GlobSet>,
+    exclude: GlobSet,
+    gitignore: bool,
+}
+
+impl Walker {
+    pub fn new(options: &WalkOptions) -> Result<Walker, globset::Error> {
+        let include = if options.include.is_empty() {
+            None
+        } else {
+            Some(build_glob_set(&options.include)?)
+        };
+
+        Ok(Walker {
+            include,
+            exclude: build_glob_set(&options.exclude)?,
+            gitignore: options.gitignore,
+        })
+    }
+
+    // Files are returned in file name order. A directory matching an exclude glob is not entered.
+    pub fn files(&self, root: &Path) -> Vec<Result<PathBuf, ignore::Error>> {
+        let exclude = self.exclude.clone();
+        let prefix = root.to_path_buf();
+
+        let walk = WalkBuilder::new(root)
+            .standard_filters(false)
+            .git_ignore(self.gitignore)
+            .git_global(self.gitignore)
+            .git_exclude(self.gitignore)
+            .require_git(false)
+            .sort_by_file_name(|a, b| a.cmp(b))
+            .filter_entry(move |entry| {
+                let relative = entry.path().strip_prefix(&prefix).unwrap_or(entry.path());
+                relative.as_os_str().is_empty() || !matches(&exclude, relative)
+            })
+            .build();
+
+        walk.filter_map(|entry| match entry {
+            Ok(entry) if entry.file_type().is_some_and(|file_type| file_type.is_file()) => {
+                let relative = entry.path().strip_prefix(root).unwrap_or(entry.path());
+                self.is_included(relative).then(|| Ok(entry.into_path()))
+            }
+            Ok(_) => None,
+            Err(error) => Some(Err(error)),
+        })
+        .collect()
+    }
+
+    fn is_included(&self, relative: &Path) -> bool {
+        self.include.as_ref().is_none_or(|include| matches(include, relative))
+    }
+}
+
+fn matches(globs: &GlobSet, relative: &Path) -> bool {
+    globs.is_match(relative) || relative.file_name().is_some_and(|name| globs.is_match(name))
+}
+
+fn build_glob_set(globs: &[&String]) -> Result<GlobSet, globset::Error> {
+    let mut builder = GlobSetBuilder::new();
+    for glob in globs {
+        builder.add(Glob::new(glob)?);
+    }
+    builder.build()
+}
+
+pub fn is_binary(path: &Path) -> io::Result<bool> {
+    let mut buffer = Vec::with_capacity(BINARY_CHECK_SIZE);
+    File::open(path)?.take(BINARY_CHECK_SIZE as u64).read_to_end(&mut buffer)?;
+    Ok(buffer.contains(&0))
+}
+
+#[cfg(test)]
+mod walker_tests {
+    use super::*;
+    use std::{env, fs};
+
+    fn create_tree(name: &str) -> PathBuf {
+        let root = env::temp_dir().join(format!("ccwc-walk-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        for (path, contents) in [
+            ("src/main.rs", "fn main() {}\n"),
+            ("src/lib/mod.rs", "pub mod lib;\n"),
+            ("src/notes.md", "# notes\n"),
+            ("target/debug/build.rs", "fn build() {}\n"),
+            ("ignored.rs", "// ignored\n"),
+            (".gitignore", "ignored.rs\n"),
+        ] {
+            let path = root.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, contents).unwrap();
+        }
+        root
+    }
+
+    fn relative_files(walker: &Walker, root: &Path) -> Vec<String> {
+        walker
+            .files(root)
+            .into_iter()
+            .map(|file| file.unwrap().strip_prefix(root).unwrap().to_string_lossy().into_owned())
+            .collect()
+    }
+
+    #[test]
+    fn files_matches_include_and_exclude_globs() {
+        // Arrange
+        let root = create_tree("globs");
+        let include = "*.rs".to_string();
+        let exclude = "debug".to_string();
+        let walker = Walker::new(&WalkOptions { include: vec![&include], exclude: vec![&exclude], gitignore: false }).unwrap();
+
+        //Act
+        let files = relative_files(&walker, &root);
+
+        //Assert
+        assert_eq!(files, vec!["ignored.rs", "src/lib/mod.rs", "src/main.rs"]);
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn files_honours_gitignore_when_asked() {
+        // Arrange
+        let root = create_tree("gitignore");
+        let exclude = "target/**".to_string();
+        let walker = Walker::new(&WalkOptions { include: vec![], exclude: vec![&exclude], gitignore: true }).unwrap();
+
+        //Act
+        let files = relative_files(&walker, &root);
+
+        //Assert
+        assert_eq!(files, vec![".gitignore", "src/lib/mod.rs", "src/main.rs", "src/notes.md"]);
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn is_binary_detects_nul_bytes() {
+        // Arrange
+        let path = env::temp_dir().join(format!("ccwc-binary-{}", std::process::id()));
+        fs::write(&path, b"\x7fELF\x00\x01").unwrap();
+
+        //Act
+        let binary = is_binary(&path).unwrap();
+        let text = is_binary(Path::new("test_files/pg123.txt")).unwrap();
+
+        //Assert
+        assert!(binary);
+        assert!(!text);
+        fs::remove_file(path).unwrap();
+    }
+}
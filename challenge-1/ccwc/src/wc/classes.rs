@@ -0,0 +1,147 @@
+use std::ops::AddAssign;
+
+use super::unicode::Utf8Decoder;
+
+// How many characters fall in each class. Every character is exactly one of a letter, a digit,
+// punctuation, whitespace or a control character. Non-ASCII characters and NUL bytes are also
+// counted on their own, so they overlap with the other classes. Combining marks that aren't
+// alphabetic, and format characters such as U+200B, are punctuation. An invalid UTF-8 sequence
+// is read as U+FFFD, which is non-ASCII punctuation.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CharClasses {
+    pub letters: u64,
+    pub digits: u64,
+    // Punctuation and symbols: anything that is not a letter, digit, whitespace or control character.
+    pub punctuation: u64,
+    pub whitespace: u64,
+    pub control: u64,
+    pub non_ascii: u64,
+    pub nul: u64,
+}
+
+impl CharClasses {
+    fn record(&mut self, char: char) {
+        if char.is_alphabetic() {
+            self.letters += 1;
+        } else if char.is_numeric() {
+            self.digits += 1;
+        } else if char.is_whitespace() {
+            self.whitespace += 1;
+        } else if char.is_control() {
+            self.control += 1;
+        } else {
+            self.punctuation += 1;
+        }
+
+        if !char.is_ascii() {
+            self.non_ascii += 1;
+        }
+        if char == '\0' {
+            self.nul += 1;
+        }
+    }
+}
+
+impl AddAssign for CharClasses {
+    fn add_assign(&mut self, other: CharClasses) {
+        self.letters += other.letters;
+        self.digits += other.digits;
+        self.punctuation += other.punctuation;
+        self.whitespace += other.whitespace;
+        self.control += other.control;
+        self.non_ascii += other.non_ascii;
+        self.nul += other.nul;
+    }
+}
+
+#[derive(Clone, Default)]
+pub(super) struct ClassCounter {
+    decoder: Utf8Decoder,
+    pub(super) classes: CharClasses,
+}
+
+impl ClassCounter {
+    pub(super) fn feed(&mut self, buffer: &[u8]) {
+        let classes = &mut self.classes;
+        self.decoder.decode(buffer, &mut |char| classes.record(char));
+    }
+
+    pub(super) fn finish(&mut self) {
+        let classes = &mut self.classes;
+        self.decoder.finish(&mut |char| classes.record(char));
+    }
+}
+
+#[cfg(test)]
+mod class_counter_tests {
+    use super::*;
+
+    fn classify(input: &str) -> CharClasses {
+        let mut counter = ClassCounter::default();
+        counter.feed(input.as_bytes());
+        counter.finish();
+        counter.classes
+    }
+
+    #[test]
+    fn each_character_has_one_class() {
+        // Arrange
+        let input = "Caf\u{e9} 42\u{661}, \u{4e16}!\t\u{a0}\0\x07\u{1f600}\n";
+
+        //Act
+        let classes = classify(input);
+
+        //Assert
+        assert_eq!(classes, CharClasses { letters: 5, digits: 3, punctuation: 3, whitespace: 5, control: 2, non_ascii: 5, nul: 1 });
+    }
+
+    #[test]
+    fn line_separators_are_whitespace() {
+        // Arrange
+        let input = "\u{2028}\u{2029}\u{85}\u{3000}";
+
+        //Act
+        let classes = classify(input);
+
+        //Assert
+        assert_eq!(classes, CharClasses { whitespace: 4, non_ascii: 4, ..CharClasses::default() });
+    }
+
+    #[test]
+    fn delete_is_a_control_character() {
+        // Arrange
+        let input = "\x7f\x1b\u{9f}";
+
+        //Act
+        let classes = classify(input);
+
+        //Assert
+        assert_eq!(classes, CharClasses { control: 3, non_ascii: 1, ..CharClasses::default() });
+    }
+
+    #[test]
+    fn marks_are_letters_only_when_alphabetic() {
+        // Arrange
+        // A combining acute accent is punctuation, but a Devanagari vowel sign is alphabetic.
+        let input = "e\u{301}\u{915}\u{93f}\u{200b}";
+
+        //Act
+        let classes = classify(input);
+
+        //Assert
+        assert_eq!(classes, CharClasses { letters: 3, punctuation: 2, non_ascii: 4, ..CharClasses::default() });
+    }
+
+    #[test]
+    fn invalid_sequences_are_non_ascii_punctuation() {
+        // Arrange
+        let mut counter = ClassCounter::default();
+
+        //Act
+        counter.feed(b"a\xff\xe2\x82");
+        counter.finish();
+
+        //Assert
+        assert_eq!(counter.classes, CharClasses { letters: 1, punctuation: 2, non_ascii: 2, ..CharClasses::default() });
+    }
+}
@@ -0,0 +1,388 @@
+use std::{ops::AddAssign, path::Path};
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum Language {
+    Rust,
+    C,
+    Cpp,
+    Python,
+    Shell,
+    Json,
+    Markdown,
+}
+
+// How comments and strings are written. A comment marker inside a string is not a comment.
+#[derive(Clone, Copy)]
+struct Syntax {
+    line_comments: &'static [&'static str],
+    block_comment: Option<(&'static str, &'static str)>,
+    // Rust block comments nest, so `/* /* */ */` is one comment.
+    nested: bool,
+    quotes: &'static [u8],
+    // In shell a `#` only starts a comment at the start of a word, so `$#` is not a comment.
+    comment_starts_word: bool,
+    // In Rust a `'` is either a character literal, such as `'"'` or `'\''`, or a lifetime.
+    char_literals: bool,
+    // Python strings in three quotes can span lines. One that starts a line is a docstring,
+    // which is a comment.
+    triple_quotes: bool,
+}
+
+impl Language {
+    pub const ALL: [Language; 7] = [
+        Language::Rust,
+        Language::C,
+        Language::Cpp,
+        Language::Python,
+        Language::Shell,
+        Language::Json,
+        Language::Markdown,
+    ];
+
+    pub fn from_path(path: &Path) -> Option<Language> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        Language::ALL.into_iter().find(|language| language.extensions().contains(&extension.as_str()))
+    }
+
+    pub fn from_name(name: &str) -> Option<Language> {
+        Language::ALL.into_iter().find(|language| language.name().eq_ignore_ascii_case(name) || language.id() == name)
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Language::Rust => "Rust",
+            Language::C => "C",
+            Language::Cpp => "C++",
+            Language::Python => "Python",
+            Language::Shell => "Shell",
+            Language::Json => "JSON",
+            Language::Markdown => "Markdown",
+        }
+    }
+
+    // The name used on the command line.
+    pub fn id(&self) -> &'static str {
+        match self {
+            Language::Rust => "rust",
+            Language::C => "c",
+            Language::Cpp => "cpp",
+            Language::Python => "python",
+            Language::Shell => "shell",
+            Language::Json => "json",
+            Language::Markdown => "markdown",
+        }
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        match self {
+            Language::Rust => &["rs"],
+            Language::C => &["c", "h"],
+            Language::Cpp => &["cc", "cpp", "cxx", "hh", "hpp", "hxx"],
+            Language::Python => &["py", "pyi"],
+            Language::Shell => &["sh", "bash", "zsh"],
+            Language::Json => &["json"],
+            Language::Markdown => &["md", "markdown"],
+        }
+    }
+
+    fn syntax(&self) -> Syntax {
+        match self {
+            Language::Rust => Syntax { line_comments: &["//"], block_comment: Some(("/*", "*/")), nested: true, quotes: b"\"", comment_starts_word: false, char_literals: true, triple_quotes: false },
+            Language::C | Language::Cpp => Syntax { line_comments: &["//"], block_comment: Some(("/*", "*/")), nested: false, quotes: b"\"'", comment_starts_word: false, char_literals: false, triple_quotes: false },
+            Language::Python => Syntax { line_comments: &["#"], block_comment: None, nested: false, quotes: b"\"'", comment_starts_word: false, char_literals: false, triple_quotes: true },
+            Language::Shell => Syntax { line_comments: &["#"], block_comment: None, nested: false, quotes: b"\"'", comment_starts_word: true, char_literals: false, triple_quotes: false },
+            Language::Json => Syntax { line_comments: &[], block_comment: None, nested: false, quotes: b"\"", comment_starts_word: false, char_literals: false, triple_quotes: false },
+            Language::Markdown => Syntax { line_comments: &[], block_comment: Some(("<!--", "-->")), nested: false, quotes: b"", comment_starts_word: false, char_literals: false, triple_quotes: false },
+        }
+    }
+}
+
+// A line with anything outside a comment is code, even if it ends with a comment. A line with
+// only whitespace is blank, even inside a block comment.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CodeLines {
+    pub code: u64,
+    pub comment: u64,
+    pub blank: u64,
+}
+
+impl AddAssign for CodeLines {
+    fn add_assign(&mut self, other: CodeLines) {
+        self.code += other.code;
+        self.comment += other.comment;
+        self.blank += other.blank;
+    }
+}
+
+// Classifies whole lines, so the end of the current line is held back until the next buffer
+// completes it. The depth of the block comment the last line ended in is carried to the next
+// line, and so is a Python string in three quotes. Other strings are assumed to end on the line
+// they start on, so a stray quote can't hide the rest of the file.
+#[derive(Clone)]
+pub(super) struct CodeCounter {
+    syntax: Syntax,
+    line: Vec<u8>,
+    comment_depth: u32,
+    // The quote character of the string in three quotes the last line ended in, and whether it
+    // is a docstring.
+    long_string: Option<(u8, bool)>,
+    pub(super) code_lines: CodeLines,
+}
+
+impl CodeCounter {
+    pub(super) fn new(language: Language) -> Self {
+        CodeCounter {
+            syntax: language.syntax(),
+            line: Vec::new(),
+            comment_depth: 0,
+            long_string: None,
+            code_lines: CodeLines::default(),
+        }
+    }
+
+    pub(super) fn feed(&mut self, buffer: &[u8]) {
+        let mut rest = buffer;
+        while let Some(end) = rest.iter().position(|&byte| byte == b'\n') {
+            if self.line.is_empty() {
+                self.classify(&rest[..end]);
+            } else {
+                let mut line = std::mem::take(&mut self.line);
+                line.extend_from_slice(&rest[..end]);
+                self.classify(&line);
+                line.clear();
+                self.line = line;
+            }
+            rest = &rest[end + 1..];
+        }
+        self.line.extend_from_slice(rest);
+    }
+
+    // A final line without a line feed is still classified.
+    pub(super) fn finish(&mut self) {
+        if !self.line.is_empty() {
+            let line = std::mem::take(&mut self.line);
+            self.classify(&line);
+        }
+    }
+
+    fn classify(&mut self, line: &[u8]) {
+        let syntax = self.syntax;
+        let mut code = false;
+        let mut comment = false;
+        let mut quote: Option<u8> = None;
+        let mut index = 0;
+
+        while index < line.len() {
+            let rest = &line[index..];
+            let byte = line[index];
+
+            if let Some((start, end)) = syntax.block_comment.filter(|_| self.comment_depth > 0) {
+                if syntax.nested && rest.starts_with(start.as_bytes()) {
+                    self.comment_depth += 1;
+                    index += start.len();
+                } else if rest.starts_with(end.as_bytes()) {
+                    self.comment_depth -= 1;
+                    index += end.len();
+                } else {
+                    index += 1;
+                }
+                comment |= !byte.is_ascii_whitespace();
+                continue;
+            }
+
+            if let Some((open, docstring)) = self.long_string {
+                if rest.starts_with(&[open; 3]) {
+                    self.long_string = None;
+                    index += 3;
+                } else {
+                    index += if byte == b'\\' { 2 } else { 1 };
+                }
+                if docstring {
+                    comment |= !byte.is_ascii_whitespace();
+                } else {
+                    code |= !byte.is_ascii_whitespace();
+                }
+                continue;
+            }
+
+            if let Some(open) = quote {
+                if byte == b'\\' {
+                    index += 1;
+                } else if byte == open {
+                    quote = None;
+                }
+                index += 1;
+                continue;
+            }
+
+            if byte.is_ascii_whitespace() {
+                index += 1;
+                continue;
+            }
+
+            let word_start = index == 0 || line[index - 1].is_ascii_whitespace();
+            if syntax.line_comments.iter().any(|marker| rest.starts_with(marker.as_bytes()))
+                && (word_start || !syntax.comment_starts_word)
+            {
+                comment = true;
+                break;
+            }
+            if let Some((start, _)) = syntax.block_comment.filter(|(start, _)| rest.starts_with(start.as_bytes())) {
+                self.comment_depth = 1;
+                comment = true;
+                index += start.len();
+                continue;
+            }
+
+            if syntax.triple_quotes && syntax.quotes.contains(&byte) && rest.starts_with(&[byte; 3]) {
+                self.long_string = Some((byte, !code));
+                comment |= !code;
+                index += 3;
+                continue;
+            }
+            if syntax.char_literals && byte == b'\'' {
+                index += char_literal_length(rest).unwrap_or(1);
+                code = true;
+                continue;
+            }
+            if syntax.quotes.contains(&byte) {
+                quote = Some(byte);
+            }
+            code = true;
+            index += 1;
+        }
+
+        if code {
+            self.code_lines.code += 1;
+        } else if comment {
+            self.code_lines.comment += 1;
+        } else {
+            self.code_lines.blank += 1;
+        }
+    }
+}
+
+// The length of the character literal at the start of `rest`, such as `'x'`, `'\''` or `'\u{e9}'`,
+// or None when the quote starts a lifetime such as `'a`.
+fn char_literal_length(rest: &[u8]) -> Option<usize> {
+    match rest.get(1)? {
+        b'\\' => rest.iter().skip(3).take(10).position(|&byte| byte == b'\'').map(|offset| offset + 4),
+        &first => {
+            let end = 1 + match first {
+                0xf0.. => 4,
+                0xe0.. => 3,
+                0xc0.. => 2,
+                _ => 1,
+            };
+            (rest.get(end) == Some(&b'\'')).then_some(end + 1)
+        }
+    }
+}
+
+#[cfg(test)]
+mod code_counter_tests {
+    use super::*;
+
+    fn classify(input: &str, language: Language, piece_size: usize) -> (u64, u64, u64) {
+        let mut counter = CodeCounter::new(language);
+        for piece in input.as_bytes().chunks(piece_size) {
+            counter.feed(piece);
+        }
+        counter.finish();
+        (counter.code_lines.code, counter.code_lines.comment, counter.code_lines.blank)
+    }
+
+    #[test]
+    fn rust_block_comments_nest_and_span_buffers() {
+        // Arrange
+        let input = "// heading\nfn main() { /* inline */\n\n    /* outer\n    /* inner */\n\n    still comment */ let x = \"/* not a comment\";\n}\n/**/";
+
+        for piece_size in 1..=input.len() {
+            //Act
+            let lines = classify(input, Language::Rust, piece_size);
+
+            //Assert
+            assert_eq!(lines, (3, 4, 2), "piece size {}", piece_size);
+        }
+    }
+
+    #[test]
+    fn c_block_comments_do_not_nest() {
+        // Arrange
+        let input = "/* one /* two */\nint x = '\"'; // trailing\n*/\n";
+
+        //Act
+        let lines = classify(input, Language::C, 5);
+
+        //Assert
+        assert_eq!(lines, (2, 1, 0));
+    }
+
+    #[test]
+    fn rust_char_literals_are_not_strings() {
+        // Arrange
+        let input = "let q = '\"'; /* c\nstill comment */\nfn f<'a>(x: &'a str) -> char { '\\'' } // c\nlet u = '\\u{e9}'; let e = '\u{e9}'; // c\n";
+
+        //Act
+        let lines = classify(input, Language::Rust, 7);
+
+        //Assert
+        assert_eq!(lines, (3, 1, 0));
+    }
+
+    #[test]
+    fn python_docstrings_are_comments() {
+        // Arrange
+        let input = "def f():\n    \"\"\"Docstring.\n\n    x = 1 # not code\n    \"\"\"\n    s = '''\n# not a comment\n'''\n    return s\n";
+
+        for piece_size in 1..=input.len() {
+            //Act
+            let lines = classify(input, Language::Python, piece_size);
+
+            //Assert
+            assert_eq!(lines, (5, 3, 1), "piece size {}", piece_size);
+        }
+    }
+
+    #[test]
+    fn python_and_shell_hash_comments() {
+        // Arrange
+        let python = "# comment\nx = \"# not a comment\"\n\n";
+        let shell = "#!/bin/sh\necho $# words\n  # indented\n";
+
+        //Act
+        let python_lines = classify(python, Language::Python, 4);
+        let shell_lines = classify(shell, Language::Shell, 4);
+
+        //Assert
+        assert_eq!(python_lines, (1, 1, 1));
+        assert_eq!(shell_lines, (1, 2, 0));
+    }
+
+    #[test]
+    fn markdown_html_comments_and_json() {
+        // Arrange
+        let markdown = "# Title\n<!-- hidden\nstill hidden -->\n\ntext\r\n";
+        let json = "{\n  \"url\": \"http://example.com\"\n}\n";
+
+        //Act
+        let markdown_lines = classify(markdown, Language::Markdown, 3);
+        let json_lines = classify(json, Language::Json, 3);
+
+        //Assert
+        assert_eq!(markdown_lines, (2, 2, 1));
+        assert_eq!(json_lines, (3, 0, 0));
+    }
+
+    #[test]
+    fn language_from_path_uses_the_extension() {
+        // Arrange
+        let paths = ["src/main.rs", "include/list.H", "tool.hpp", "README.md", "notes.txt", "Makefile"];
+
+        //Act
+        let languages: Vec<Option<Language>> = paths.iter().map(|path| Language::from_path(Path::new(path))).collect();
+
+        //Assert
+        assert_eq!(languages, vec![Some(Language::Rust), Some(Language::C), Some(Language::Cpp), Some(Language::Markdown), None, None]);
+    }
+}
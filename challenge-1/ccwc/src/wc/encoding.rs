@@ -0,0 +1,220 @@
+use std::borrow::Cow;
+
+const REPLACEMENT_CHARACTER: char = '\u{FFFD}';
+const BYTE_ORDER_MARK: char = '\u{FEFF}';
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Encoding {
+    // UTF-16 when the input starts with a UTF-16 byte order mark, otherwise UTF-8.
+    #[default]
+    Auto,
+    Utf8,
+    Utf16Le,
+    Utf16Be,
+    // ISO-8859-1, where every byte is the code point of the same value.
+    Latin1,
+}
+
+// The UTF-16 encoding named by a byte order mark at the start of `bytes`.
+pub(super) fn sniff(bytes: &[u8]) -> Option<Encoding> {
+    match bytes {
+        [0xFF, 0xFE, ..] => Some(Encoding::Utf16Le),
+        [0xFE, 0xFF, ..] => Some(Encoding::Utf16Be),
+        _ => None,
+    }
+}
+
+// Converts the input to UTF-8 a buffer at a time, so the rest of the counting only ever sees UTF-8.
+// A code unit or surrogate pair cut in half by the end of a buffer is held back until the next
+// buffer completes it. A UTF-16 byte order mark is dropped rather than counted as a character.
+#[derive(Clone)]
+pub(super) struct Transcoder {
+    encoding: Encoding,
+    pending: Vec<u8>,
+    high_surrogate: Option<u16>,
+    at_start: bool,
+}
+
+impl Transcoder {
+    pub(super) fn new(encoding: Encoding) -> Self {
+        Transcoder {
+            encoding,
+            pending: Vec::new(),
+            high_surrogate: None,
+            at_start: true,
+        }
+    }
+
+    // Once the input is known to be UTF-8 nothing more needs converting.
+    pub(super) fn is_passthrough(&self) -> bool {
+        self.encoding == Encoding::Utf8 && self.pending.is_empty()
+    }
+
+    pub(super) fn transcode<'a>(&mut self, input: &'a [u8]) -> Cow<'a, [u8]> {
+        if self.encoding == Encoding::Auto {
+            if self.pending.is_empty() && input.len() >= 2 {
+                self.encoding = sniff(input).unwrap_or(Encoding::Utf8);
+                return self.transcode(input);
+            }
+
+            self.pending.extend_from_slice(input);
+            if self.pending.len() < 2 {
+                return Cow::Borrowed(&[]);
+            }
+            self.encoding = sniff(&self.pending).unwrap_or(Encoding::Utf8);
+            let joined = std::mem::take(&mut self.pending);
+            return Cow::Owned(self.transcode(&joined).into_owned());
+        }
+
+        let mut output = String::new();
+        match self.encoding {
+            Encoding::Auto | Encoding::Utf8 => return Cow::Borrowed(input),
+            Encoding::Latin1 => output.extend(input.iter().map(|&byte| byte as char)),
+            Encoding::Utf16Le | Encoding::Utf16Be => self.decode_utf16(input, &mut output),
+        }
+        Cow::Owned(output.into_bytes())
+    }
+
+    // Anything still held back at the end of the input. A lone byte, or a high surrogate without
+    // its low surrogate, is invalid.
+    pub(super) fn finish(&mut self) -> Vec<u8> {
+        let mut output = String::new();
+        match self.encoding {
+            Encoding::Auto | Encoding::Utf8 => return std::mem::take(&mut self.pending),
+            Encoding::Latin1 => {}
+            Encoding::Utf16Le | Encoding::Utf16Be => {
+                if self.high_surrogate.take().is_some() {
+                    output.push(REPLACEMENT_CHARACTER);
+                }
+                if !self.pending.is_empty() {
+                    self.pending.clear();
+                    output.push(REPLACEMENT_CHARACTER);
+                }
+            }
+        }
+        output.into_bytes()
+    }
+
+    fn decode_utf16(&mut self, input: &[u8], output: &mut String) {
+        let mut input = input;
+        if let Some(&first) = self.pending.first() {
+            let Some((&second, rest)) = input.split_first() else {
+                return;
+            };
+            self.pending.clear();
+            self.decode_unit([first, second], output);
+            input = rest;
+        }
+
+        let mut units = input.chunks_exact(2);
+        for unit in &mut units {
+            self.decode_unit([unit[0], unit[1]], output);
+        }
+        self.pending.extend_from_slice(units.remainder());
+    }
+
+    fn decode_unit(&mut self, bytes: [u8; 2], output: &mut String) {
+        let unit = match self.encoding {
+            Encoding::Utf16Be => u16::from_be_bytes(bytes),
+            _ => u16::from_le_bytes(bytes),
+        };
+
+        if let Some(high) = self.high_surrogate.take() {
+            if (0xDC00..=0xDFFF).contains(&unit) {
+                let code_point = 0x10000 + ((high as u32 - 0xD800) << 10) + (unit as u32 - 0xDC00);
+                output.push(char::from_u32(code_point).unwrap());
+                return;
+            }
+            output.push(REPLACEMENT_CHARACTER);
+        }
+
+        match unit {
+            0xD800..=0xDBFF => self.high_surrogate = Some(unit),
+            0xDC00..=0xDFFF => output.push(REPLACEMENT_CHARACTER),
+            _ => {
+                let char = char::from_u32(unit as u32).unwrap();
+                if !(self.at_start && char == BYTE_ORDER_MARK) {
+                    output.push(char);
+                }
+            }
+        }
+        self.at_start = false;
+    }
+}
+
+#[cfg(test)]
+mod transcoder_tests {
+    use super::*;
+
+    fn transcode_in_pieces(input: &[u8], encoding: Encoding, piece_size: usize) -> String {
+        let mut transcoder = Transcoder::new(encoding);
+        let mut output = Vec::new();
+        for piece in input.chunks(piece_size) {
+            output.extend_from_slice(&transcoder.transcode(piece));
+        }
+        output.extend_from_slice(&transcoder.finish());
+        String::from_utf8(output).unwrap()
+    }
+
+    fn utf16(text: &str, bom: bool, big_endian: bool) -> Vec<u8> {
+        let units = bom.then_some(0xFEFF).into_iter().chain(text.encode_utf16());
+        units.flat_map(|unit| if big_endian { unit.to_be_bytes() } else { unit.to_le_bytes() }).collect()
+    }
+
+    #[test]
+    fn auto_detects_utf16_from_the_byte_order_mark() {
+        // Arrange
+        let text = "h\u{e9}llo \u{4e16}\u{754c}\n\u{1f600}";
+        let little_endian = utf16(text, true, false);
+        let big_endian = utf16(text, true, true);
+
+        for piece_size in 1..=little_endian.len() {
+            //Act
+            let from_little_endian = transcode_in_pieces(&little_endian, Encoding::Auto, piece_size);
+            let from_big_endian = transcode_in_pieces(&big_endian, Encoding::Auto, piece_size);
+
+            //Assert
+            assert_eq!(from_little_endian, text, "piece size {}", piece_size);
+            assert_eq!(from_big_endian, text, "piece size {}", piece_size);
+        }
+    }
+
+    #[test]
+    fn auto_without_byte_order_mark_is_utf8() {
+        // Arrange
+        let text = "h\u{e9}llo";
+
+        for piece_size in 1..=text.len() {
+            //Act
+            let output = transcode_in_pieces(text.as_bytes(), Encoding::Auto, piece_size);
+
+            //Assert
+            assert_eq!(output, text, "piece size {}", piece_size);
+        }
+        assert_eq!(transcode_in_pieces(b"a", Encoding::Auto, 1), "a");
+    }
+
+    #[test]
+    fn unpaired_surrogates_and_odd_bytes_are_replaced() {
+        // Arrange
+        let input = [0x3D, 0xD8, 0x41, 0x00, 0x00, 0xDC, 0x3D, 0xD8, 0x00, 0xDE, 0x42];
+
+        //Act
+        let output = transcode_in_pieces(&input, Encoding::Utf16Le, 3);
+
+        //Assert
+        assert_eq!(output, "\u{FFFD}A\u{FFFD}\u{1F600}\u{FFFD}");
+    }
+
+    #[test]
+    fn latin1_maps_every_byte() {
+        // Arrange
+        let input = b"caf\xe9 \xa3\xff";
+
+        //Act
+        let output = transcode_in_pieces(input, Encoding::Latin1, 2);
+
+        //Assert
+        assert_eq!(output, "caf\u{e9} \u{a3}\u{ff}");
+    }
+}
@@ -0,0 +1,33 @@
+use std::{fmt, io};
+
+#[derive(Debug)]
+pub enum Error {
+    // The input could not be opened or read.
+    Io(io::Error),
+    // A thread counting part of a file with `process_file_parallel` panicked.
+    ThreadPanicked,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(error) => write!(f, "{}", error),
+            Error::ThreadPanicked => write!(f, "a counting thread panicked"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(error) => Some(error),
+            Error::ThreadPanicked => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(error: io::Error) -> Self {
+        Error::Io(error)
+    }
+}
@@ -0,0 +1,414 @@
+use std::{
+    borrow::Cow,
+    cmp::Reverse,
+    collections::{hash_map::DefaultHasher, BinaryHeap, HashMap, HashSet},
+    hash::{Hash, Hasher},
+    ops::AddAssign,
+    sync::Arc,
+};
+
+use super::is_whitespace;
+
+pub const DEFAULT_MAX_DISTINCT_WORDS: usize = 100_000;
+const SKETCH_DEPTH: usize = 4;
+const SKETCH_WIDTH: usize = 1 << 16;
+// Once the counts are estimates, this many times the number of words asked for are tracked as
+// candidates, so a word that starts slowly can still make it into the top.
+const CANDIDATE_FACTOR: usize = 4;
+const MIN_CANDIDATES: usize = 64;
+// Longer words are cut to this many bytes, so a long run without whitespace, such as a minified
+// file, isn't held in memory while it is collected.
+const MAX_WORD_LENGTH: usize = 1024;
+
+#[derive(Clone, Debug)]
+pub struct FrequencyOptions {
+    pub top: usize,
+    // Words are lower cased before they are counted.
+    pub fold_case: bool,
+    // Words that are never counted, such as "the" and "and". They are compared after case folding.
+    pub stop_words: Arc<HashSet<String>>,
+    // Counts are exact until there are more distinct words than this.
+    pub max_distinct: usize,
+}
+
+impl Default for FrequencyOptions {
+    fn default() -> Self {
+        FrequencyOptions {
+            top: 10,
+            fold_case: false,
+            stop_words: Arc::default(),
+            max_distinct: DEFAULT_MAX_DISTINCT_WORDS,
+        }
+    }
+}
+
+// A count-min sketch. Each word is counted in one cell of every row, and its estimate is the
+// smallest of those cells, so an estimate is never too low and is only too high when every
+// row has a collision with a more common word.
+#[derive(Clone, Debug, PartialEq)]
+struct CountMinSketch {
+    cells: Vec<u64>,
+}
+
+impl CountMinSketch {
+    fn new() -> Self {
+        CountMinSketch { cells: vec![0; SKETCH_DEPTH * SKETCH_WIDTH] }
+    }
+
+    // The cell in each row, found by double hashing a single 64 bit hash.
+    fn cells_for(word: &str) -> [usize; SKETCH_DEPTH] {
+        let mut hasher = DefaultHasher::new();
+        word.hash(&mut hasher);
+        let hash = hasher.finish();
+        let (first, second) = (hash as u32 as usize, (hash >> 32) as usize | 1);
+        std::array::from_fn(|row| row * SKETCH_WIDTH + first.wrapping_add(row.wrapping_mul(second)) % SKETCH_WIDTH)
+    }
+
+    fn add(&mut self, word: &str, count: u64) -> u64 {
+        let cells = Self::cells_for(word);
+        for &cell in &cells {
+            self.cells[cell] += count;
+        }
+        cells.iter().map(|&cell| self.cells[cell]).min().unwrap()
+    }
+
+    fn estimate(&self, word: &str) -> u64 {
+        Self::cells_for(word).iter().map(|&cell| self.cells[cell]).min().unwrap()
+    }
+}
+
+// How often each word appears. Counts are exact until there are more than `max_distinct`
+// different words, then the words are counted in a count-min sketch and only the most
+// frequent words seen so far are kept, so memory stays bounded whatever the input.
+#[derive(Clone, Debug)]
+pub struct WordFrequencies {
+    max_distinct: usize,
+    candidates: usize,
+    // Every word and its exact count, or once there is a sketch, the candidates for the top
+    // words and their estimated counts.
+    counts: HashMap<String, u64>,
+    // Once there is a sketch, each candidate with the smallest first, so finding the one to
+    // replace doesn't mean looking at them all. Estimates only grow, so a count here can be
+    // behind the one in `counts`. It is brought up to date when it reaches the top.
+    smallest: BinaryHeap<Reverse<(u64, String)>>,
+    sketch: Option<CountMinSketch>,
+}
+
+// `smallest` is only an index into `counts`.
+impl PartialEq for WordFrequencies {
+    fn eq(&self, other: &Self) -> bool {
+        self.max_distinct == other.max_distinct && self.candidates == other.candidates && self.counts == other.counts && self.sketch == other.sketch
+    }
+}
+
+impl WordFrequencies {
+    pub fn new(options: &FrequencyOptions) -> Self {
+        WordFrequencies {
+            max_distinct: options.max_distinct.max(1),
+            candidates: (options.top * CANDIDATE_FACTOR).max(MIN_CANDIDATES),
+            counts: HashMap::new(),
+            smallest: BinaryHeap::new(),
+            sketch: None,
+        }
+    }
+
+    // True once the counts are estimates from the sketch.
+    pub fn is_approximate(&self) -> bool {
+        self.sketch.is_some()
+    }
+
+    // The most frequent words, most frequent first. Words with the same count are in alphabetical order.
+    pub fn top(&self, n: usize) -> Vec<(String, u64)> {
+        let mut words: Vec<(String, u64)> = self.counts.iter().map(|(word, &count)| (word.clone(), count)).collect();
+        words.sort_unstable_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        words.truncate(n);
+        words
+    }
+
+    fn add(&mut self, word: &str, count: u64) {
+        let Some(sketch) = self.sketch.as_mut() else {
+            match self.counts.get_mut(word) {
+                Some(existing) => *existing += count,
+                None => {
+                    self.counts.insert(word.to_string(), count);
+                    if self.counts.len() > self.max_distinct {
+                        self.switch_to_sketch();
+                    }
+                }
+            }
+            return;
+        };
+
+        let estimate = sketch.add(word, count);
+        self.offer(word, estimate);
+    }
+
+    fn offer(&mut self, word: &str, estimate: u64) {
+        if let Some(existing) = self.counts.get_mut(word) {
+            *existing = estimate;
+            return;
+        }
+        if self.counts.len() < self.candidates {
+            self.counts.insert(word.to_string(), estimate);
+            self.smallest.push(Reverse((estimate, word.to_string())));
+            return;
+        }
+
+        if estimate > self.least_count() {
+            let Reverse((_, least)) = self.smallest.pop().unwrap();
+            self.counts.remove(&least);
+            self.counts.insert(word.to_string(), estimate);
+            self.smallest.push(Reverse((estimate, word.to_string())));
+        }
+    }
+
+    // The smallest count of any candidate, leaving that candidate at the top of `smallest`.
+    fn least_count(&mut self) -> u64 {
+        loop {
+            let Reverse((count, word)) = self.smallest.peek().unwrap();
+            let current = self.counts[word];
+            if current == *count {
+                return current;
+            }
+            let Reverse((_, word)) = self.smallest.pop().unwrap();
+            self.smallest.push(Reverse((current, word)));
+        }
+    }
+
+    fn switch_to_sketch(&mut self) {
+        let mut sketch = CountMinSketch::new();
+        for (word, &count) in &self.counts {
+            sketch.add(word, count);
+        }
+
+        let top = self.top(self.candidates);
+        self.counts = top
+            .into_iter()
+            .map(|(word, _)| {
+                let estimate = sketch.estimate(&word);
+                (word, estimate)
+            })
+            .collect();
+        self.smallest = self.counts.iter().map(|(word, &count)| Reverse((count, word.clone()))).collect();
+        self.sketch = Some(sketch);
+    }
+}
+
+impl AddAssign for WordFrequencies {
+    fn add_assign(&mut self, other: WordFrequencies) {
+        self.candidates = self.candidates.max(other.candidates);
+
+        let Some(other_sketch) = other.sketch else {
+            for (word, count) in other.counts {
+                self.add(&word, count);
+            }
+            return;
+        };
+
+        if self.sketch.is_none() {
+            self.switch_to_sketch();
+        }
+        let sketch = self.sketch.as_mut().unwrap();
+        for (cell, count) in sketch.cells.iter_mut().zip(other_sketch.cells) {
+            *cell += count;
+        }
+
+        let words: Vec<String> = self.counts.keys().cloned().chain(other.counts.into_keys()).collect();
+        self.counts.clear();
+        self.smallest.clear();
+        for word in words {
+            let estimate = self.sketch.as_ref().unwrap().estimate(&word);
+            self.offer(&word, estimate);
+        }
+    }
+}
+
+// Collects the words of the input, using the same word boundaries as the word count. The end of
+// a word cut in half by the end of a buffer is held back until the next buffer.
+#[derive(Clone)]
+pub(super) struct WordCollector {
+    options: FrequencyOptions,
+    word: Vec<u8>,
+    pub(super) frequencies: WordFrequencies,
+}
+
+impl WordCollector {
+    pub(super) fn new(options: &FrequencyOptions) -> Self {
+        WordCollector {
+            options: options.clone(),
+            word: Vec::new(),
+            frequencies: WordFrequencies::new(options),
+        }
+    }
+
+    pub(super) fn feed(&mut self, buffer: &[u8]) {
+        let mut pieces = buffer.split(|&byte| is_whitespace(byte)).peekable();
+        while let Some(piece) = pieces.next() {
+            if pieces.peek().is_none() {
+                self.hold(piece);
+            } else if !self.word.is_empty() {
+                self.hold(piece);
+                self.count_held_word();
+            } else if !piece.is_empty() {
+                self.count(piece);
+            }
+        }
+    }
+
+    pub(super) fn finish(&mut self) {
+        if !self.word.is_empty() {
+            self.count_held_word();
+        }
+    }
+
+    // Only the first `MAX_WORD_LENGTH` bytes of a word are held, since only those are counted.
+    fn hold(&mut self, piece: &[u8]) {
+        let room = MAX_WORD_LENGTH.saturating_sub(self.word.len());
+        self.word.extend_from_slice(&piece[..piece.len().min(room)]);
+    }
+
+    fn count_held_word(&mut self) {
+        let word = std::mem::take(&mut self.word);
+        self.count(&word);
+        self.word = word;
+        self.word.clear();
+    }
+
+    fn count(&mut self, bytes: &[u8]) {
+        let mut word = String::from_utf8_lossy(truncate_word(bytes));
+        if self.options.fold_case {
+            word = Cow::Owned(word.to_lowercase());
+        }
+        if !self.options.stop_words.contains(word.as_ref()) {
+            self.frequencies.add(&word, 1);
+        }
+    }
+}
+
+// The first `MAX_WORD_LENGTH` bytes of a word, less any character cut in half at the end.
+fn truncate_word(bytes: &[u8]) -> &[u8] {
+    if bytes.len() < MAX_WORD_LENGTH {
+        return bytes;
+    }
+    let truncated = &bytes[..MAX_WORD_LENGTH];
+    match std::str::from_utf8(truncated) {
+        Err(error) if error.error_len().is_none() => &truncated[..error.valid_up_to()],
+        _ => truncated,
+    }
+}
+
+#[cfg(test)]
+mod word_frequency_tests {
+    use super::*;
+
+    fn collect(input: &str, options: &FrequencyOptions, piece_size: usize) -> WordFrequencies {
+        let mut collector = WordCollector::new(options);
+        for piece in input.as_bytes().chunks(piece_size) {
+            collector.feed(piece);
+        }
+        collector.finish();
+        collector.frequencies
+    }
+
+    fn words(expected: &[(&str, u64)]) -> Vec<(String, u64)> {
+        expected.iter().map(|&(word, count)| (word.to_string(), count)).collect()
+    }
+
+    #[test]
+    fn words_split_across_buffers_are_counted_once() {
+        // Arrange
+        let input = "the cat\tsat on the\r\nmat, the cat";
+
+        for piece_size in 1..=input.len() {
+            //Act
+            let frequencies = collect(input, &FrequencyOptions::default(), piece_size);
+
+            //Assert
+            assert_eq!(frequencies.top(3), words(&[("the", 3), ("cat", 2), ("mat,", 1)]), "piece size {}", piece_size);
+        }
+    }
+
+    #[test]
+    fn case_folding_and_stop_words() {
+        // Arrange
+        let options = FrequencyOptions {
+            fold_case: true,
+            stop_words: Arc::new(HashSet::from(["the".to_string()])),
+            ..FrequencyOptions::default()
+        };
+
+        //Act
+        let frequencies = collect("The cat and THE Cat and the dog", &options, 5);
+
+        //Assert
+        assert_eq!(frequencies.top(10), words(&[("and", 2), ("cat", 2), ("dog", 1)]));
+    }
+
+    #[test]
+    fn long_words_are_cut_short() {
+        // Arrange
+        // The cut falls in the middle of an "\u{e9}", which is two bytes long.
+        let long_word = format!("x{}", "\u{e9}".repeat(MAX_WORD_LENGTH));
+        let input = format!("{} a {}", long_word, long_word);
+        let expected = format!("x{}", "\u{e9}".repeat(MAX_WORD_LENGTH / 2 - 1));
+
+        for piece_size in [1, 7, 1000, input.len()] {
+            //Act
+            let mut collector = WordCollector::new(&FrequencyOptions::default());
+            for piece in input.as_bytes().chunks(piece_size) {
+                collector.feed(piece);
+                assert!(collector.word.len() <= MAX_WORD_LENGTH);
+            }
+            collector.finish();
+
+            //Assert
+            assert_eq!(collector.frequencies.top(2), words(&[(&expected, 2), ("a", 1)]), "piece size {}", piece_size);
+        }
+    }
+
+    #[test]
+    fn too_many_distinct_words_switches_to_a_sketch() {
+        // Arrange
+        let options = FrequencyOptions { top: 3, max_distinct: 100, ..FrequencyOptions::default() };
+        let mut input = String::new();
+        for i in 0..5000 {
+            input.push_str(&format!("word{} common ", i));
+            if i % 2 == 0 {
+                input.push_str("often ");
+            }
+            if i % 5 == 0 {
+                input.push_str("sometimes ");
+            }
+        }
+
+        //Act
+        let frequencies = collect(&input, &options, 4096);
+
+        //Assert
+        assert!(frequencies.is_approximate());
+        assert!(frequencies.counts.len() <= MIN_CANDIDATES);
+        assert_eq!(frequencies.smallest.len(), frequencies.counts.len());
+        let top_words: Vec<String> = frequencies.top(3).into_iter().map(|(word, _)| word).collect();
+        assert_eq!(top_words, vec!["common", "often", "sometimes"]);
+        assert!(frequencies.top(1)[0].1 >= 5000);
+    }
+
+    #[test]
+    fn add_assign_merges_exact_and_approximate_counts() {
+        // Arrange
+        let small = FrequencyOptions { max_distinct: 10, ..FrequencyOptions::default() };
+        let mut total = collect("a b a", &FrequencyOptions::default(), 8);
+        let exact = collect("a c", &FrequencyOptions::default(), 8);
+        let approximate = collect("a a a d e f g h i j k l m n o p", &small, 8);
+
+        //Act
+        total += exact;
+        let exact_top = total.top(2);
+        total += approximate;
+
+        //Assert
+        assert_eq!(exact_top, words(&[("a", 3), ("b", 1)]));
+        assert!(total.is_approximate());
+        assert_eq!(total.top(1), words(&[("a", 6)]));
+    }
+}
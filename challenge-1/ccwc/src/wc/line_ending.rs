@@ -0,0 +1,180 @@
+use std::ops::AddAssign;
+
+// What ends a line for the line count.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum LineTerminator {
+    // A line feed, whether or not a carriage return comes before it, the same as wc.
+    #[default]
+    Lf,
+    // Only a carriage return followed by a line feed.
+    Crlf,
+    // A carriage return, as on classic Mac OS. A CRLF pair counts once.
+    Cr,
+    // A CRLF pair, or a line feed or carriage return on its own.
+    Any,
+}
+
+impl LineTerminator {
+    pub(super) fn ends_line(self, ending: LineEnding) -> bool {
+        match self {
+            LineTerminator::Lf => ending != LineEnding::Cr,
+            LineTerminator::Crlf => ending == LineEnding::Crlf,
+            LineTerminator::Cr => ending != LineEnding::Lf,
+            LineTerminator::Any => true,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(super) enum LineEnding {
+    Lf,
+    Crlf,
+    Cr,
+}
+
+impl LineEnding {
+    pub(super) fn text(self) -> &'static str {
+        match self {
+            LineEnding::Lf => "\n",
+            LineEnding::Crlf => "\r\n",
+            LineEnding::Cr => "\r",
+        }
+    }
+}
+
+// Finds the line endings in text fed in a character at a time. A line feed is found as soon as it
+// is seen, but whether a carriage return ends a line on its own is only known from the character
+// after it, so it is found then, or when the input ends. A byte is fed in as the `char` with
+// the same value.
+#[derive(Clone, Default)]
+pub(super) struct LineEndingScanner {
+    pending_cr: bool,
+}
+
+impl LineEndingScanner {
+    pub(super) fn next(&mut self, char: char) -> Option<LineEnding> {
+        if char == '\n' {
+            let ending = if self.pending_cr { LineEnding::Crlf } else { LineEnding::Lf };
+            self.pending_cr = false;
+            return Some(ending);
+        }
+
+        let ending = self.pending_cr.then_some(LineEnding::Cr);
+        self.pending_cr = char == '\r';
+        ending
+    }
+
+    // A carriage return at the very end of the input ends a line on its own.
+    pub(super) fn finish(&mut self) -> Option<LineEnding> {
+        std::mem::take(&mut self.pending_cr).then_some(LineEnding::Cr)
+    }
+}
+
+// How many lines end with each terminator. A CRLF pair only counts as CRLF, not as a CR or an LF.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LineEndings {
+    pub lf: u64,
+    pub crlf: u64,
+    pub cr: u64,
+}
+
+impl LineEndings {
+    pub fn line_count(&self, terminator: LineTerminator) -> u64 {
+        match terminator {
+            LineTerminator::Lf => self.lf + self.crlf,
+            LineTerminator::Crlf => self.crlf,
+            LineTerminator::Cr => self.cr + self.crlf,
+            LineTerminator::Any => self.lf + self.crlf + self.cr,
+        }
+    }
+
+    // True when the lines don't all end the same way.
+    pub fn is_mixed(&self) -> bool {
+        [self.lf, self.crlf, self.cr].iter().filter(|&&count| count > 0).count() > 1
+    }
+}
+
+impl AddAssign for LineEndings {
+    fn add_assign(&mut self, other: LineEndings) {
+        self.lf += other.lf;
+        self.crlf += other.crlf;
+        self.cr += other.cr;
+    }
+}
+
+// A carriage return at the end of a buffer might be the first half of a CRLF pair, so it is held
+// back until the first byte of the next buffer shows which it is.
+#[derive(Clone, Default)]
+pub(super) struct LineEndingCounter {
+    scanner: LineEndingScanner,
+    pub(super) line_endings: LineEndings,
+}
+
+impl LineEndingCounter {
+    pub(super) fn feed(&mut self, buffer: &[u8]) {
+        for &byte in buffer {
+            if let Some(ending) = self.scanner.next(byte as char) {
+                self.record(ending);
+            }
+        }
+    }
+
+    pub(super) fn finish(&mut self) {
+        if let Some(ending) = self.scanner.finish() {
+            self.record(ending);
+        }
+    }
+
+    fn record(&mut self, ending: LineEnding) {
+        match ending {
+            LineEnding::Lf => self.line_endings.lf += 1,
+            LineEnding::Crlf => self.line_endings.crlf += 1,
+            LineEnding::Cr => self.line_endings.cr += 1,
+        }
+    }
+}
+
+#[cfg(test)]
+mod line_ending_tests {
+    use super::*;
+
+    fn count(input: &[u8], piece_size: usize) -> LineEndings {
+        let mut counter = LineEndingCounter::default();
+        for piece in input.chunks(piece_size) {
+            counter.feed(piece);
+        }
+        counter.finish();
+        counter.line_endings
+    }
+
+    #[test]
+    fn crlf_pairs_split_across_buffers_count_once() {
+        // Arrange
+        let input = b"one\r\ntwo\nthree\rfour\r\n\r\r\n\n\r";
+
+        for piece_size in 1..=input.len() {
+            //Act
+            let line_endings = count(input, piece_size);
+
+            //Assert
+            assert_eq!(line_endings, LineEndings { lf: 2, crlf: 3, cr: 3 }, "piece size {}", piece_size);
+        }
+    }
+
+    #[test]
+    fn line_count_for_each_terminator() {
+        // Arrange
+        let line_endings = count(b"a\r\nb\nc\rd\r\n", 4);
+
+        //Act
+        let counts: Vec<u64> = [LineTerminator::Lf, LineTerminator::Crlf, LineTerminator::Cr, LineTerminator::Any]
+            .iter()
+            .map(|&terminator| line_endings.line_count(terminator))
+            .collect();
+
+        //Assert
+        assert_eq!(counts, vec![3, 2, 3, 4]);
+        assert!(line_endings.is_mixed());
+        assert!(!count(b"a\r\nb\r\n", 4).is_mixed());
+    }
+}
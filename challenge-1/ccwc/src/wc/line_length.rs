@@ -0,0 +1,276 @@
+use std::{collections::BTreeMap, ops::AddAssign};
+
+use unicode_width::UnicodeWidthChar;
+
+use super::line_ending::{LineEnding, LineEndingScanner, LineTerminator};
+use super::unicode::Utf8Decoder;
+
+pub const HISTOGRAM_BUCKET_WIDTH: u64 = 10;
+const TAB_WIDTH: u64 = 8;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum LineLengthUnit {
+    Bytes,
+    Chars,
+    // The width of the line on a terminal. Tabs move to the next multiple of 8 columns and wide
+    // East Asian characters take up two columns.
+    #[default]
+    Columns,
+}
+
+// Lengths never include the line feed, carriage return or CRLF pair that ends a line. Any line
+// feed or carriage return that doesn't end a line for the terminator is part of the line. A
+// final line without a terminator is still counted.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LineLengths {
+    pub lines: u64,
+    pub min: u64,
+    pub max: u64,
+    pub total: u64,
+    // The number of lines in each bucket of `HISTOGRAM_BUCKET_WIDTH`, keyed by the smallest length in the bucket.
+    pub histogram: BTreeMap<u64, u64>,
+}
+
+impl LineLengths {
+    pub fn mean(&self) -> f64 {
+        if self.lines == 0 {
+            return 0.0;
+        }
+        self.total as f64 / self.lines as f64
+    }
+
+    fn record(&mut self, length: u64) {
+        self.min = if self.lines == 0 { length } else { self.min.min(length) };
+        self.max = self.max.max(length);
+        self.total += length;
+        self.lines += 1;
+        *self.histogram.entry(length - length % HISTOGRAM_BUCKET_WIDTH).or_insert(0) += 1;
+    }
+}
+
+impl AddAssign for LineLengths {
+    fn add_assign(&mut self, other: LineLengths) {
+        if other.lines == 0 {
+            return;
+        }
+
+        self.min = if self.lines == 0 { other.min } else { self.min.min(other.min) };
+        self.max = self.max.max(other.max);
+        self.total += other.total;
+        self.lines += other.lines;
+        for (bucket, count) in other.histogram {
+            *self.histogram.entry(bucket).or_insert(0) += count;
+        }
+    }
+}
+
+// Measures each line as it is read. The length of the current line is carried from one buffer to
+// the next, the same way `previous_byte` is for words.
+#[derive(Clone)]
+pub(super) struct LineLengthCounter {
+    unit: LineLengthUnit,
+    terminator: LineTerminator,
+    decoder: Utf8Decoder,
+    // A carriage return is only added to the line once the scanner shows it doesn't end it. It
+    // can be the last byte of one buffer and the line feed the first of the next.
+    scanner: LineEndingScanner,
+    line_length: u64,
+    line_started: bool,
+    pub(super) line_lengths: LineLengths,
+}
+
+impl LineLengthCounter {
+    pub(super) fn new(unit: LineLengthUnit, terminator: LineTerminator) -> Self {
+        LineLengthCounter {
+            unit,
+            terminator,
+            decoder: Utf8Decoder::default(),
+            scanner: LineEndingScanner::default(),
+            line_length: 0,
+            line_started: false,
+            line_lengths: LineLengths::default(),
+        }
+    }
+
+    pub(super) fn feed(&mut self, buffer: &[u8]) {
+        if self.unit == LineLengthUnit::Bytes {
+            for &byte in buffer {
+                self.count_char(byte as char);
+            }
+            return;
+        }
+
+        let mut decoder = std::mem::take(&mut self.decoder);
+        decoder.decode(buffer, &mut |char| self.count_char(char));
+        self.decoder = decoder;
+    }
+
+    pub(super) fn finish(&mut self) {
+        let mut decoder = std::mem::take(&mut self.decoder);
+        decoder.finish(&mut |char| self.count_char(char));
+
+        if let Some(ending) = self.scanner.finish() {
+            self.count_ending(ending);
+        }
+        if self.line_started {
+            self.end_line();
+        }
+    }
+
+    fn count_char(&mut self, char: char) {
+        if let Some(ending) = self.scanner.next(char) {
+            self.count_ending(ending);
+        }
+
+        match char {
+            // Part of the line ending just counted.
+            '\n' => {}
+            // Held back by the scanner.
+            '\r' => self.line_started = true,
+            _ => {
+                self.line_started = true;
+                self.add_char(char);
+            }
+        }
+    }
+
+    fn count_ending(&mut self, ending: LineEnding) {
+        if self.terminator.ends_line(ending) {
+            self.end_line();
+            return;
+        }
+
+        self.line_started = true;
+        for char in ending.text().chars() {
+            self.add_char(char);
+        }
+    }
+
+    fn end_line(&mut self) {
+        self.line_lengths.record(self.line_length);
+        self.line_length = 0;
+        self.line_started = false;
+    }
+
+    fn add_char(&mut self, char: char) {
+        self.line_length += match (self.unit, char) {
+            (LineLengthUnit::Columns, '\t') => TAB_WIDTH - self.line_length % TAB_WIDTH,
+            (LineLengthUnit::Columns, _) => char.width().unwrap_or(0) as u64,
+            _ => 1,
+        };
+    }
+}
+
+#[cfg(test)]
+mod line_length_tests {
+    use super::*;
+
+    fn measure(input: &str, unit: LineLengthUnit, piece_size: usize) -> LineLengths {
+        measure_lines(input, unit, LineTerminator::Lf, piece_size)
+    }
+
+    fn measure_lines(input: &str, unit: LineLengthUnit, terminator: LineTerminator, piece_size: usize) -> LineLengths {
+        let mut counter = LineLengthCounter::new(unit, terminator);
+        for piece in input.as_bytes().chunks(piece_size) {
+            counter.feed(piece);
+        }
+        counter.finish();
+        counter.line_lengths
+    }
+
+    #[test]
+    fn lengths_in_bytes_chars_and_columns() {
+        // Arrange
+        let input = "h\u{e9}llo\n\u{4e16}\u{754c}\r\na\tb\n";
+
+        for piece_size in 1..=input.len() {
+            //Act
+            let bytes = measure(input, LineLengthUnit::Bytes, piece_size);
+            let chars = measure(input, LineLengthUnit::Chars, piece_size);
+            let columns = measure(input, LineLengthUnit::Columns, piece_size);
+
+            //Assert
+            assert_eq!((bytes.min, bytes.max, bytes.total), (3, 6, 15), "piece size {}", piece_size);
+            assert_eq!((chars.min, chars.max, chars.total), (2, 5, 10), "piece size {}", piece_size);
+            assert_eq!((columns.min, columns.max, columns.total), (4, 9, 18), "piece size {}", piece_size);
+        }
+    }
+
+    #[test]
+    fn only_a_carriage_return_before_a_line_feed_is_left_out() {
+        // Arrange
+        let input = "a\rb\r\nc\r";
+
+        for piece_size in 1..=input.len() {
+            //Act
+            let lengths = measure(input, LineLengthUnit::Bytes, piece_size);
+
+            //Assert
+            assert_eq!((lengths.lines, lengths.max, lengths.total), (2, 3, 5), "piece size {}", piece_size);
+        }
+    }
+
+    #[test]
+    fn carriage_returns_end_lines_with_cr_terminator() {
+        // Arrange
+        let input = "one\rthree\r\nfive\nsix\r";
+
+        for piece_size in 1..=input.len() {
+            //Act
+            let cr = measure_lines(input, LineLengthUnit::Bytes, LineTerminator::Cr, piece_size);
+            let any = measure_lines(input, LineLengthUnit::Bytes, LineTerminator::Any, piece_size);
+            let crlf = measure_lines(input, LineLengthUnit::Bytes, LineTerminator::Crlf, piece_size);
+
+            //Assert
+            assert_eq!((cr.lines, cr.max, cr.total), (3, 8, 16), "piece size {}", piece_size);
+            assert_eq!((any.lines, any.max, any.total), (4, 5, 15), "piece size {}", piece_size);
+            assert_eq!((crlf.lines, crlf.max, crlf.total), (2, 9, 18), "piece size {}", piece_size);
+        }
+    }
+
+    #[test]
+    fn final_line_without_line_feed_is_counted() {
+        // Arrange
+        let input = "one\n\nthree";
+
+        //Act
+        let lengths = measure(input, LineLengthUnit::Bytes, 4);
+
+        //Assert
+        assert_eq!(lengths.lines, 3);
+        assert_eq!(lengths.min, 0);
+        assert_eq!(lengths.max, 5);
+        assert_eq!(lengths.mean(), 8.0 / 3.0);
+    }
+
+    #[test]
+    fn histogram_groups_lengths_into_buckets() {
+        // Arrange
+        let input = format!("{}\n{}\n{}\n", "a".repeat(3), "a".repeat(9), "a".repeat(25));
+
+        //Act
+        let lengths = measure(&input, LineLengthUnit::Bytes, 8);
+
+        //Assert
+        assert_eq!(lengths.histogram, BTreeMap::from([(0, 2), (20, 1)]));
+    }
+
+    #[test]
+    fn add_assign_merges_lengths() {
+        // Arrange
+        let mut total = measure("abc\nabcdefghijkl\n", LineLengthUnit::Bytes, 64);
+        let empty = LineLengths::default();
+        let other = measure("a\n", LineLengthUnit::Bytes, 64);
+
+        //Act
+        total += empty;
+        total += other;
+
+        //Assert
+        assert_eq!(total.lines, 3);
+        assert_eq!(total.min, 1);
+        assert_eq!(total.max, 12);
+        assert_eq!(total.total, 16);
+        assert_eq!(total.histogram, BTreeMap::from([(0, 2), (10, 1)]));
+    }
+}
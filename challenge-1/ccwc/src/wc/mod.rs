@@ -1,80 +1,479 @@
-use std::{error::Error, fs::File, io::Read};
-
+mod classes;
+mod code;
+mod encoding;
+mod error;
+mod frequency;
+mod line_ending;
+mod line_length;
+mod parallel;
+mod select;
+mod text_stats;
+mod tokens;
+mod unicode;
+mod validate;
+
+use std::{
+    fs::File,
+    io::{self, Read, Seek, SeekFrom},
+    ops::AddAssign,
+    path::Path,
+    sync::Arc,
+};
+
+use classes::ClassCounter;
+pub use classes::CharClasses;
+use code::CodeCounter;
+pub use code::{CodeLines, Language};
+pub use encoding::Encoding;
+use encoding::Transcoder;
+pub use error::Error;
+use frequency::WordCollector;
+pub use frequency::{FrequencyOptions, WordFrequencies, DEFAULT_MAX_DISTINCT_WORDS};
+
+use line_ending::LineEndingCounter;
+pub use line_ending::{LineEndings, LineTerminator};
+use line_length::LineLengthCounter;
+pub use line_length::{LineLengthUnit, LineLengths, HISTOGRAM_BUCKET_WIDTH};
+pub use parallel::process_file_parallel;
+use select::LineSelector;
+use text_stats::TextStatsCounter;
+pub use text_stats::TextStats;
+use tokens::TokenCounter;
+pub use tokens::Vocabulary;
+use unicode::{GraphemeCounter, UnicodeCounter};
+use validate::Utf8Validator;
+pub use validate::{Utf8ErrorKind, Utf8Problem};
+
+#[derive(Clone, Debug, Default, PartialEq)]
 pub struct Counts {
     pub byte_count: u64,
+    // The size of the input before it was decompressed. The counter only ever sees the
+    // decompressed bytes, so this is filled in by whatever decompressed them.
+    pub compressed_bytes: u64,
     pub char_count: u64,
     pub word_count: u64,
     pub line_count: u64,
+    pub graphemes: u64,
+    pub token_count: u64,
+    pub line_lengths: LineLengths,
+    pub line_endings: LineEndings,
+    pub code_lines: CodeLines,
+    pub classes: CharClasses,
+    pub text_stats: TextStats,
+    pub word_frequencies: Option<WordFrequencies>,
+    pub invalid_utf8: u64,
+    // The first invalid UTF-8 sequences in this input. They are not carried into a total, since
+    // their positions only make sense in the input they came from.
+    pub utf8_problems: Vec<Utf8Problem>,
 }
 
-pub fn process_file(filepath: String) -> Result<Counts, Box<dyn Error>> {
-    let file_open_result = File::open(filepath);
-    if file_open_result.is_err() {
-        return Err(Box::new(file_open_result.err().unwrap()));
+impl AddAssign for Counts {
+    fn add_assign(&mut self, other: Counts) {
+        self.byte_count += other.byte_count;
+        self.compressed_bytes += other.compressed_bytes;
+        self.char_count += other.char_count;
+        self.word_count += other.word_count;
+        self.line_count += other.line_count;
+        self.graphemes += other.graphemes;
+        self.token_count += other.token_count;
+        self.line_lengths += other.line_lengths;
+        self.line_endings += other.line_endings;
+        self.code_lines += other.code_lines;
+        self.classes += other.classes;
+        self.text_stats += other.text_stats;
+        match (self.word_frequencies.as_mut(), other.word_frequencies) {
+            (Some(frequencies), Some(other)) => *frequencies += other,
+            (None, other) => self.word_frequencies = other,
+            (Some(_), None) => {}
+        }
+        self.invalid_utf8 += other.invalid_utf8;
     }
-    let result = process_reader(&file_open_result.unwrap());
-    result
 }
 
-pub fn process_reader<R: Read>(mut reader: R) -> Result<Counts, Box<dyn Error>> {
-    const BUFFER_SIZE: usize = 1024;
-    let mut buffer: [u8; BUFFER_SIZE] = [0; BUFFER_SIZE];
-    let mut line_count: u64 = 0;
-    let mut word_count: u64 = 0;
-    let mut char_count: u64 = 0;
-    let mut byte_count: u64 = 0;
+pub const DEFAULT_BUFFER_SIZE: usize = 64 * 1024;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum TextMode {
+    // Words are separated by space, tab, CR and LF. Characters are counted from UTF-8 lead bytes.
+    #[default]
+    Ascii,
+    // The input is decoded as UTF-8 and words are separated by any Unicode whitespace.
+    Unicode,
+    // The input is decoded as UTF-8 and words are found with UAX #29 word segmentation.
+    Uax29,
+}
 
-    let mut previous_byte: u8 = b' ';
-    loop {
-        let file_read_result = reader.read(&mut buffer);
-        if file_read_result.is_err() {
-            return Err(Box::new(file_read_result.err().unwrap()));
+#[derive(Clone)]
+pub struct Options {
+    pub buffer_size: usize,
+    pub text_mode: TextMode,
+    pub graphemes: bool,
+    // Tokens are only counted when a vocabulary is given.
+    pub tokens: Option<Arc<Vocabulary>>,
+    // Line lengths are only measured when a unit is given.
+    pub line_lengths: Option<LineLengthUnit>,
+    // What ends a line for `line_count`.
+    pub line_terminator: LineTerminator,
+    // The lines ending with LF, CRLF and CR are counted separately when asked for, or when lines
+    // don't just end with LF.
+    pub line_endings: bool,
+    // The input is only checked for invalid UTF-8 when the number of problems to keep is given.
+    // The bytes checked are the input's own, before they are decoded with `encoding`.
+    pub validate_utf8: Option<usize>,
+    pub encoding: Encoding,
+    // Lines are only classified as code, comment or blank when the language is given.
+    pub language: Option<Language>,
+    // Characters are only counted by class when asked for.
+    pub classes: bool,
+    // Sentences, paragraphs and syllables are only counted when asked for.
+    pub text_stats: bool,
+    // How often each word appears is only collected when asked for.
+    pub word_frequencies: Option<FrequencyOptions>,
+    // Only part of the input is counted. The bytes before `skip_bytes` are skipped, then at most
+    // `limit_bytes` are read, and of those only the lines from `from_line` to `to_line`,
+    // numbered from 1, are counted.
+    pub skip_bytes: u64,
+    pub limit_bytes: Option<u64>,
+    pub from_line: Option<u64>,
+    pub to_line: Option<u64>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            buffer_size: DEFAULT_BUFFER_SIZE,
+            text_mode: TextMode::Ascii,
+            graphemes: false,
+            tokens: None,
+            line_lengths: None,
+            line_terminator: LineTerminator::Lf,
+            line_endings: false,
+            validate_utf8: None,
+            encoding: Encoding::Auto,
+            language: None,
+            classes: false,
+            text_stats: false,
+            word_frequencies: None,
+            skip_bytes: 0,
+            limit_bytes: None,
+            from_line: None,
+            to_line: None,
         }
-        let read_count: usize = file_read_result.unwrap();
+    }
+}
+
+impl Options {
+    fn counts_line_endings(&self) -> bool {
+        self.line_endings || self.line_terminator != LineTerminator::Lf
+    }
+}
+
+pub fn process_file(filepath: String) -> Result<Counts, Error> {
+    let file = File::open(filepath)?;
+    process_reader(&file)
+}
+
+// The same as `process_reader_with_options`, but a regular file seeks past `skip_bytes` rather
+// than reading them.
+pub fn process_file_with_options(filepath: &Path, options: &Options) -> Result<Counts, Error> {
+    let file = File::open(filepath)?;
+    if options.skip_bytes == 0 || !file.metadata()?.is_file() {
+        return Ok(count_reader(file, options)?);
+    }
+
+    Ok(count_seekable(file, options)?)
+}
+
+pub fn process_reader<R: Read>(reader: R) -> Result<Counts, Error> {
+    process_reader_with_options(reader, &Options::default())
+}
+
+pub fn process_reader_with_options<R: Read>(reader: R, options: &Options) -> Result<Counts, Error> {
+    Ok(count_reader(reader, options)?)
+}
+
+fn count_seekable<R: Read + Seek>(mut reader: R, options: &Options) -> io::Result<Counts> {
+    reader.seek(SeekFrom::Start(options.skip_bytes))?;
+    count_reader(reader, &Options { skip_bytes: 0, ..options.clone() })
+}
+
+fn count_reader<R: Read>(mut reader: R, options: &Options) -> io::Result<Counts> {
+    if options.skip_bytes > 0 {
+        io::copy(&mut reader.by_ref().take(options.skip_bytes), &mut io::sink())?;
+    }
+    let mut reader = reader.take(options.limit_bytes.unwrap_or(u64::MAX));
+    let mut buffer: Vec<u8> = vec![0; options.buffer_size.max(1)];
+    let mut counter = Counter::new(options);
+    let mut selector = (options.from_line.is_some() || options.to_line.is_some())
+        .then(|| LineSelector::new(options.from_line, options.to_line, options.line_terminator));
+
+    loop {
+        let read_count: usize = reader.read(&mut buffer)?;
         if read_count == 0 {
             break;
         }
-        byte_count += read_count as u64;
+        match selector.as_mut() {
+            None => counter.feed(&buffer[0..read_count]),
+            Some(selector) => {
+                counter.feed(selector.select(&buffer[0..read_count]));
+                if selector.is_done() {
+                    break;
+                }
+            }
+        }
+    }
 
-        let result = process_buffer(buffer[0..read_count].as_ref(), previous_byte);
+    Ok(counter.finish())
+}
+
+// A count in progress. Input can be fed in as it arrives, in pieces of any size, and the
+// state that spans pieces (the last byte for words, partly decoded characters and so on)
+// is carried from one piece to the next.
+#[derive(Clone)]
+pub struct Counter {
+    counts: Counts,
+    merged: Counts,
+    previous_byte: u8,
+    // None once the input is known to be UTF-8.
+    transcoder: Option<Transcoder>,
+    unicode_counter: Option<UnicodeCounter>,
+    grapheme_counter: Option<GraphemeCounter>,
+    token_counter: Option<TokenCounter>,
+    line_length_counter: Option<LineLengthCounter>,
+    line_terminator: LineTerminator,
+    line_ending_counter: Option<LineEndingCounter>,
+    code_counter: Option<CodeCounter>,
+    class_counter: Option<ClassCounter>,
+    text_stats_counter: Option<TextStatsCounter>,
+    word_collector: Option<WordCollector>,
+    validator: Option<Utf8Validator>,
+}
+
+impl Counter {
+    pub fn new(options: &Options) -> Self {
+        Counter {
+            counts: Counts::default(),
+            merged: Counts::default(),
+            previous_byte: b' ',
+            transcoder: (options.encoding != Encoding::Utf8).then(|| Transcoder::new(options.encoding)),
+            unicode_counter: match options.text_mode {
+                TextMode::Ascii => None,
+                mode => Some(UnicodeCounter::new(mode)),
+            },
+            grapheme_counter: options.graphemes.then(GraphemeCounter::default),
+            token_counter: options.tokens.as_ref().map(TokenCounter::new),
+            line_length_counter: options.line_lengths.map(|unit| LineLengthCounter::new(unit, options.line_terminator)),
+            line_terminator: options.line_terminator,
+            line_ending_counter: options.counts_line_endings().then(LineEndingCounter::default),
+            code_counter: options.language.map(CodeCounter::new),
+            class_counter: options.classes.then(ClassCounter::default),
+            text_stats_counter: options.text_stats.then(TextStatsCounter::default),
+            word_collector: options.word_frequencies.as_ref().map(WordCollector::new),
+            validator: options.validate_utf8.map(Utf8Validator::new),
+        }
+    }
+
+    // `byte_count` is the number of bytes fed in, and they are checked for invalid UTF-8 as they
+    // are. Everything else is counted once the input has been converted to UTF-8.
+    pub fn feed(&mut self, buffer: &[u8]) {
+        if buffer.is_empty() {
+            return;
+        }
+        self.counts.byte_count += buffer.len() as u64;
+        if let Some(validator) = self.validator.as_mut() {
+            validator.feed(buffer);
+        }
 
-        line_count += result.0;
-        word_count += result.1;
-        char_count += result.2;
-        previous_byte = result.3;
+        match self.transcoder.take() {
+            None => self.count_text(buffer),
+            Some(mut transcoder) => {
+                self.count_text(&transcoder.transcode(buffer));
+                if !transcoder.is_passthrough() {
+                    self.transcoder = Some(transcoder);
+                }
+            }
+        }
     }
 
-    Ok(Counts {
-        byte_count,
-        char_count,
-        line_count,
-        word_count,
-    })
+    fn count_text(&mut self, buffer: &[u8]) {
+        if buffer.is_empty() {
+            return;
+        }
+
+        let result = process_buffer(buffer, self.previous_byte);
+
+        self.counts.line_count += result.0;
+        self.counts.word_count += result.1;
+        self.counts.char_count += result.2;
+        self.previous_byte = result.3;
+
+        if let Some(counter) = self.unicode_counter.as_mut() {
+            counter.feed(buffer);
+        }
+        if let Some(counter) = self.grapheme_counter.as_mut() {
+            counter.feed(buffer);
+        }
+        if let Some(counter) = self.token_counter.as_mut() {
+            counter.feed(buffer);
+        }
+        if let Some(counter) = self.line_length_counter.as_mut() {
+            counter.feed(buffer);
+        }
+        if let Some(counter) = self.line_ending_counter.as_mut() {
+            counter.feed(buffer);
+        }
+        if let Some(counter) = self.code_counter.as_mut() {
+            counter.feed(buffer);
+        }
+        if let Some(counter) = self.class_counter.as_mut() {
+            counter.feed(buffer);
+        }
+        if let Some(counter) = self.text_stats_counter.as_mut() {
+            counter.feed(buffer);
+        }
+        if let Some(collector) = self.word_collector.as_mut() {
+            collector.feed(buffer);
+        }
+    }
+
+    // Adds counts made elsewhere, for example by another `Counter` on another thread, to the
+    // result. They are treated as a separate input, so a word is never joined across the two.
+    pub fn merge(&mut self, counts: Counts) {
+        self.merged += counts;
+    }
+
+    // The counts once the input has ended. Clone the counter first to see the counts so far
+    // and carry on feeding it.
+    pub fn finish(mut self) -> Counts {
+        if let Some(mut transcoder) = self.transcoder.take() {
+            self.count_text(&transcoder.finish());
+        }
+        if let Some(mut counter) = self.unicode_counter {
+            counter.finish();
+            self.counts.char_count = counter.char_count;
+            self.counts.word_count = counter.word_count;
+        }
+        if let Some(mut counter) = self.grapheme_counter {
+            counter.finish();
+            self.counts.graphemes = counter.grapheme_count;
+        }
+        if let Some(mut counter) = self.token_counter {
+            counter.finish();
+            self.counts.token_count = counter.token_count;
+        }
+        if let Some(mut counter) = self.line_length_counter {
+            counter.finish();
+            self.counts.line_lengths = counter.line_lengths;
+        }
+        if let Some(mut counter) = self.line_ending_counter {
+            counter.finish();
+            self.counts.line_count = counter.line_endings.line_count(self.line_terminator);
+            self.counts.line_endings = counter.line_endings;
+        }
+        if let Some(mut counter) = self.code_counter {
+            counter.finish();
+            self.counts.code_lines = counter.code_lines;
+        }
+        if let Some(mut counter) = self.class_counter {
+            counter.finish();
+            self.counts.classes = counter.classes;
+        }
+        if let Some(mut counter) = self.text_stats_counter {
+            counter.finish();
+            self.counts.text_stats = counter.text_stats;
+        }
+        if let Some(mut collector) = self.word_collector {
+            collector.finish();
+            self.counts.word_frequencies = Some(collector.frequencies);
+        }
+        if let Some(mut validator) = self.validator {
+            validator.finish();
+            self.counts.invalid_utf8 = validator.invalid_count;
+            self.counts.utf8_problems = validator.problems;
+        }
+        self.counts += self.merged;
+        self.counts
+    }
 }
 
+impl Default for Counter {
+    fn default() -> Self {
+        Counter::new(&Options::default())
+    }
+}
+
+// Counts a word (8 bytes) of the buffer at a time. Each test produces a mask with the high bit
+// of every matching byte set, so a whole word is classified with a handful of integer operations
+// and counted with popcount. Any bytes left over at the end are counted one at a time.
 fn process_buffer(buffer: &[u8], previous_buffer_end_byte: u8) -> (u64, u64, u64, u8) {
     let mut line_count: u64 = 0;
     let mut word_count: u64 = 0;
     let mut char_count: u64 = 0;
 
-    let mut previous_byte: u8 = previous_buffer_end_byte.clone();
-    for i in 0..buffer.len() {
-        if is_new_line(buffer[i]) {
+    let mut words = buffer.chunks_exact(SWAR_WIDTH);
+    let mut previous_whitespace: u64 = is_whitespace(previous_buffer_end_byte) as u64;
+    for chunk in &mut words {
+        let word = u64::from_le_bytes(chunk.try_into().unwrap());
+
+        let new_lines = bytes_equal(word, b'\n');
+        let whitespace = new_lines | bytes_equal(word, b' ') | bytes_equal(word, b'\t') | bytes_equal(word, b'\r');
+        let utf8_continuations = word & !(word << 1) & HIGH_BITS;
+        let previous_bytes_whitespace = (whitespace << 8) | (previous_whitespace << 7);
+        let word_starts = previous_bytes_whitespace & !whitespace & HIGH_BITS;
+
+        line_count += new_lines.count_ones() as u64;
+        char_count += (SWAR_WIDTH as u32 - utf8_continuations.count_ones()) as u64;
+        word_count += word_starts.count_ones() as u64;
+        previous_whitespace = whitespace >> 63;
+    }
+
+    let remainder = words.remainder();
+    if remainder.is_empty() {
+        let last_byte = *buffer.last().unwrap_or(&previous_buffer_end_byte);
+        return (line_count, word_count, char_count, last_byte);
+    }
+
+    let counted = buffer.len() - remainder.len();
+    let previous_byte = if counted == 0 { previous_buffer_end_byte } else { buffer[counted - 1] };
+    let tail = process_buffer_scalar(remainder, previous_byte);
+
+    (line_count + tail.0, word_count + tail.1, char_count + tail.2, tail.3)
+}
+
+fn process_buffer_scalar(buffer: &[u8], previous_buffer_end_byte: u8) -> (u64, u64, u64, u8) {
+    let mut line_count: u64 = 0;
+    let mut word_count: u64 = 0;
+    let mut char_count: u64 = 0;
+
+    let mut previous_byte: u8 = previous_buffer_end_byte;
+    for &byte in buffer {
+        if is_new_line(byte) {
             line_count += 1;
         }
 
-        if is_new_utf8_char(buffer[i]) {
+        if is_new_utf8_char(byte) {
             char_count += 1;
         }
 
-        if is_new_word(previous_byte, buffer[i]) {
+        if is_new_word(previous_byte, byte) {
             word_count += 1;
         }
         
-        previous_byte = buffer[i];
+        previous_byte = byte;
     }
 
-    (line_count, word_count, char_count, buffer[buffer.len() - 1])
+    (line_count, word_count, char_count, previous_byte)
+}
+
+const SWAR_WIDTH: usize = 8;
+const LOW_BYTES: u64 = 0x0101_0101_0101_0101;
+const LOW_BITS: u64 = 0x7F7F_7F7F_7F7F_7F7F;
+const HIGH_BITS: u64 = 0x8080_8080_8080_8080;
+
+// Sets the high bit of each byte in the word that equals the given byte, and clears every other bit.
+fn bytes_equal(word: u64, byte: u8) -> u64 {
+    let difference = word ^ (LOW_BYTES * byte as u64);
+    !(((difference & LOW_BITS) + LOW_BITS) | difference | LOW_BITS)
 }
 
 fn is_new_word(previous_byte: u8, current_byte: u8) -> bool {
@@ -82,17 +481,11 @@ fn is_new_word(previous_byte: u8, current_byte: u8) -> bool {
 }
 
 fn is_whitespace(byte: u8) -> bool {
-    match byte {
-        b' ' | b'\t' | b'\r' | b'\n' => true,
-        _ => false,
-    }
+    matches!(byte, b' ' | b'\t' | b'\r' | b'\n')
 }
 
 fn is_new_line(byte: u8) -> bool {
-    match byte {
-        b'\n' => true,
-        _ => false,
-    }
+    byte == b'\n'
 }
 
 fn is_new_utf8_char(byte: u8) -> bool {
@@ -209,6 +602,105 @@ mod process_buffer_tests {
     }
 }
 
+#[cfg(test)]
+mod swar_tests {
+    use super::*;
+
+    #[test]
+    fn bytes_equal_marks_only_matching_bytes() {
+        // Arrange
+        let word = u64::from_le_bytes(*b"a\nb\n\x80\x0a\xff\x00");
+
+        //Act
+        let mask = bytes_equal(word, b'\n');
+
+        //Assert
+        assert_eq!(mask, u64::from_le_bytes([0, 0x80, 0, 0x80, 0, 0x80, 0, 0]));
+    }
+
+    #[test]
+    fn process_buffer_word_spanning_eight_byte_boundary() {
+        // Arrange
+        let input = b"one two three\tfour  five";
+
+        //Act
+        let (line_count, word_count, char_count, last_byte) = process_buffer(input, b' ');
+
+        //Assert
+        assert_eq!(line_count, 0);
+        assert_eq!(word_count, 5);
+        assert_eq!(char_count, 24);
+        assert_eq!(last_byte, b'e');
+    }
+
+    #[test]
+    fn process_buffer_continues_word_from_previous_buffer() {
+        // Arrange
+        let input = b"llo world";
+
+        //Act
+        let (_, word_count, _, _) = process_buffer(input, b'e');
+
+        //Assert
+        assert_eq!(word_count, 1);
+    }
+
+    #[test]
+    fn process_buffer_matches_scalar_on_test_file() {
+        // Arrange
+        let contents = std::fs::read("test_files/pg123.txt").unwrap();
+
+        for start in 0..SWAR_WIDTH {
+            for length in [0, 1, 7, 8, 9, 63, 64, 65, 1000, contents.len() - start] {
+                let buffer = &contents[start..start + length];
+                for previous_byte in [b' ', b'x', 0xC3] {
+                    //Act
+                    let swar = process_buffer(buffer, previous_byte);
+                    let scalar = process_buffer_scalar(buffer, previous_byte);
+
+                    //Assert
+                    assert_eq!(swar, scalar, "start {}, length {}", start, length);
+                }
+            }
+        }
+    }
+}
+
+// Run with `make bench`.
+#[cfg(test)]
+mod process_buffer_bench {
+    use super::*;
+    use std::time::Instant;
+
+    const ITERATIONS: u32 = 200;
+
+    type Kernel = fn(&[u8], u8) -> (u64, u64, u64, u8);
+
+    fn throughput(contents: &[u8], kernel: Kernel) -> f64 {
+        let start = Instant::now();
+        for _ in 0..ITERATIONS {
+            for buffer in contents.chunks(DEFAULT_BUFFER_SIZE) {
+                std::hint::black_box(kernel(std::hint::black_box(buffer), b' '));
+            }
+        }
+        let bytes = contents.len() as f64 * ITERATIONS as f64;
+        bytes / start.elapsed().as_secs_f64() / (1024.0 * 1024.0)
+    }
+
+    #[test]
+    #[ignore]
+    fn bench_process_buffer() {
+        let contents = std::fs::read("test_files/pg123.txt").unwrap();
+
+        let scalar = throughput(&contents, process_buffer_scalar);
+        let swar = throughput(&contents, process_buffer);
+
+        println!("process_buffer_scalar: {:>8.1} MiB/s", scalar);
+        println!("process_buffer:        {:>8.1} MiB/s", swar);
+        println!("speedup:               {:>8.1}x", swar / scalar);
+    }
+}
+
 #[cfg(test)]
 mod reader_tests {
     use super::*;
@@ -277,4 +769,230 @@ mod reader_tests {
         assert_eq!(counts.line_count, 1);
         assert_eq!(counts.word_count, 0);
     }
+
+    #[test]
+    fn process_reader_unicode_mode_is_independent_of_buffer_size() {
+        // Arrange
+        let data = "caf\u{e9}\u{a0}cr\u{e8}me \u{4e16}\u{754c}\u{3000}end\n".as_bytes();
+
+        for buffer_size in 1..=data.len() {
+            let options = Options { buffer_size, text_mode: TextMode::Unicode, ..Options::default() };
+
+            //Act
+            let counts = process_reader_with_options(Cursor::new(data.to_vec()), &options).unwrap();
+
+            //Assert
+            assert_eq!(counts.byte_count, 27);
+            assert_eq!(counts.char_count, 18);
+            assert_eq!(counts.line_count, 1);
+            assert_eq!(counts.word_count, 4);
+        }
+    }
+
+    #[test]
+    fn process_reader_counts_utf16_but_keeps_the_raw_byte_count() {
+        // Arrange
+        let text = "caf\u{e9} cr\u{e8}me\n\u{4e16}\u{754c}\n";
+        let data: Vec<u8> = [0xFEFF].into_iter().chain(text.encode_utf16()).flat_map(u16::to_le_bytes).collect();
+
+        for buffer_size in 1..=data.len() {
+            let options = Options { buffer_size, ..Options::default() };
+
+            //Act
+            let counts = process_reader_with_options(Cursor::new(data.clone()), &options).unwrap();
+
+            //Assert
+            assert_eq!(counts.byte_count, 30);
+            assert_eq!(counts.char_count, 14);
+            assert_eq!(counts.line_count, 2);
+            assert_eq!(counts.word_count, 3);
+        }
+    }
+
+    #[test]
+    fn validating_utf8_checks_the_bytes_before_they_are_decoded() {
+        // Arrange
+        let data = b"\xff\xfeh\x00i\x00 \x00t\x00o\x00";
+        let options = Options { validate_utf8: Some(10), ..Options::default() };
+
+        //Act
+        let counts = process_reader_with_options(Cursor::new(data.to_vec()), &options).unwrap();
+        let unchecked = process_reader(Cursor::new(data.to_vec())).unwrap();
+
+        //Assert
+        assert_eq!((counts.char_count, counts.word_count), (5, 2));
+        assert_eq!(Counts { invalid_utf8: 0, utf8_problems: vec![], ..counts.clone() }, unchecked);
+        assert_eq!(counts.invalid_utf8, 2);
+        assert_eq!(counts.utf8_problems.iter().map(|problem| problem.offset).collect::<Vec<_>>(), vec![0, 1]);
+    }
+
+    #[test]
+    fn process_reader_latin1() {
+        // Arrange
+        let data = b"na\xefve caf\xe9\n";
+        let options = Options { encoding: Encoding::Latin1, ..Options::default() };
+
+        //Act
+        let counts = process_reader_with_options(Cursor::new(data.to_vec()), &options).unwrap();
+
+        //Assert
+        assert_eq!(counts.byte_count, 11);
+        assert_eq!(counts.char_count, 11);
+        assert_eq!(counts.line_count, 1);
+        assert_eq!(counts.word_count, 2);
+    }
+
+    #[test]
+    fn process_reader_counts_a_byte_range() {
+        // Arrange
+        let data = b"header\none two\nthree\n";
+        let options = Options { skip_bytes: 7, limit_bytes: Some(8), buffer_size: 3, ..Options::default() };
+
+        //Act
+        let counts = process_reader_with_options(Cursor::new(data.to_vec()), &options).unwrap();
+
+        //Assert
+        assert_eq!(counts, Counts { byte_count: 8, char_count: 8, word_count: 2, line_count: 1, ..Counts::default() });
+    }
+
+    #[test]
+    fn process_reader_counts_selected_lines() {
+        // Arrange
+        let data = b"header\none two\nthree\nfooter\n";
+        let options = Options { from_line: Some(2), to_line: Some(3), buffer_size: 4, ..Options::default() };
+
+        //Act
+        let counts = process_reader_with_options(Cursor::new(data.to_vec()), &options).unwrap();
+
+        //Assert
+        assert_eq!(counts, Counts { byte_count: 14, char_count: 14, word_count: 3, line_count: 2, ..Counts::default() });
+    }
+
+    // Fails any read from before `start`, so only a reader that seeked past it can be counted.
+    struct ReadFrom {
+        cursor: Cursor<Vec<u8>>,
+        start: u64,
+    }
+
+    impl Read for ReadFrom {
+        fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+            if self.cursor.position() < self.start {
+                return Err(io::Error::other("read before the start"));
+            }
+            self.cursor.read(buffer)
+        }
+    }
+
+    impl Seek for ReadFrom {
+        fn seek(&mut self, position: SeekFrom) -> io::Result<u64> {
+            self.cursor.seek(position)
+        }
+    }
+
+    #[test]
+    fn skipped_bytes_of_a_seekable_reader_are_never_read() {
+        // Arrange
+        let reader = ReadFrom { cursor: Cursor::new(b"header\none two\n".to_vec()), start: 7 };
+        let options = Options { skip_bytes: 7, ..Options::default() };
+
+        //Act
+        let counts = count_seekable(reader, &options).unwrap();
+
+        //Assert
+        assert_eq!(counts, Counts { byte_count: 8, char_count: 8, word_count: 2, line_count: 1, ..Counts::default() });
+    }
+
+    #[test]
+    fn process_file_skips_to_the_same_counts_as_reading() {
+        // Arrange
+        let path = Path::new("test_files/pg123.txt");
+        let options = Options { skip_bytes: 100_000, limit_bytes: Some(50_000), ..Options::default() };
+
+        //Act
+        let seeked = process_file_with_options(path, &options).unwrap();
+        let read = process_reader_with_options(File::open(path).unwrap(), &options).unwrap();
+
+        //Assert
+        assert_eq!(seeked, read);
+        assert_eq!(seeked.byte_count, 50_000);
+    }
+}
+
+#[cfg(test)]
+mod counts_tests {
+    use super::*;
+
+    #[test]
+    fn add_assign_sums_every_count() {
+        // Arrange
+        let mut total = Counts { byte_count: 5, char_count: 4, word_count: 2, line_count: 1, graphemes: 4, ..Counts::default() };
+        let other = Counts { byte_count: 10, char_count: 9, word_count: 3, line_count: 2, graphemes: 8, ..Counts::default() };
+
+        //Act
+        total += other;
+
+        //Assert
+        assert_eq!(total.byte_count, 15);
+        assert_eq!(total.char_count, 13);
+        assert_eq!(total.word_count, 5);
+        assert_eq!(total.line_count, 3);
+        assert_eq!(total.graphemes, 12);
+    }
+}
+
+#[cfg(test)]
+mod counter_tests {
+    use super::*;
+
+    #[test]
+    fn feed_in_pieces_matches_one_read() {
+        // Arrange
+        let input = "caf\u{e9} cr\u{e8}me\r\n\u{4e16}\u{754c} hello  world\n";
+        let options = Options { text_mode: TextMode::Unicode, graphemes: true, line_lengths: Some(LineLengthUnit::Chars), ..Options::default() };
+        let expected = process_reader_with_options(input.as_bytes(), &options).unwrap();
+
+        for piece_size in 1..=input.len() {
+            //Act
+            let mut counter = Counter::new(&options);
+            for piece in input.as_bytes().chunks(piece_size) {
+                counter.feed(piece);
+            }
+            let counts = counter.finish();
+
+            //Assert
+            assert_eq!(counts, expected, "piece size {}", piece_size);
+        }
+    }
+
+    #[test]
+    fn merge_adds_partial_counts() {
+        // Arrange
+        let mut counter = Counter::default();
+        let partial = process_reader("three more words\n".as_bytes()).unwrap();
+
+        //Act
+        counter.feed(b"two wor");
+        counter.feed(b"ds\n");
+        counter.merge(partial);
+        let counts = counter.finish();
+
+        //Assert
+        assert_eq!(counts, Counts { byte_count: 27, char_count: 27, word_count: 5, line_count: 2, ..Counts::default() });
+    }
+
+    #[test]
+    fn classic_mac_lines_are_counted_with_cr_terminator() {
+        // Arrange
+        let input = "one\rtwo\rthree\r".as_bytes();
+        let options = Options { line_terminator: LineTerminator::Cr, ..Options::default() };
+
+        //Act
+        let default_counts = process_reader(input).unwrap();
+        let counts = process_reader_with_options(input, &options).unwrap();
+
+        //Assert
+        assert_eq!(default_counts.line_count, 0);
+        assert_eq!(counts.line_count, 3);
+        assert_eq!(counts.line_endings, LineEndings { lf: 0, crlf: 0, cr: 3 });
+    }
 }
@@ -0,0 +1,288 @@
+use std::{
+    fs::File,
+    io::{self, Read, Seek, SeekFrom},
+    path::Path,
+    thread,
+};
+
+use super::{count_reader, encoding, is_whitespace, process_file_with_options, Counts, Encoding, Error, Options, TextMode};
+
+// Files are not split into chunks smaller than this, the cost of spawning a thread outweighs the work.
+const MIN_CHUNK_SIZE: u64 = 64 * 1024;
+
+// A byte range of the file, along with the bytes either side of its start so the
+// partial counts can be stitched back together.
+struct Chunk {
+    start: u64,
+    end: u64,
+    previous_byte: u8,
+    first_byte: u8,
+}
+
+pub fn process_file_parallel(filepath: &Path, threads: usize, options: &Options) -> Result<Counts, Error> {
+    if !can_split(options) || is_utf16(filepath, options)? {
+        return process_file_with_options(filepath, options);
+    }
+
+    // Every chunk but the first starts part way through the file, where a byte order mark means nothing.
+    let options = Options { encoding: Encoding::Utf8, ..options.clone() };
+    let file_length = File::open(filepath)?.metadata()?.len();
+    let chunk_count = (threads as u64).min(file_length / MIN_CHUNK_SIZE).max(1);
+    process_chunks(filepath, chunk_count, &options)
+}
+
+// Only the ASCII rules can be stitched back together from the bytes either side of a boundary.
+// The Unicode modes, graphemes, tokens, line lengths, UTF-8 validation, code lines, text statistics, word
+// frequencies, other encodings and counting part of a file always count on a single thread.
+fn can_split(options: &Options) -> bool {
+    options.text_mode == TextMode::Ascii
+        && !options.graphemes
+        && options.tokens.is_none()
+        && options.line_lengths.is_none()
+        && options.validate_utf8.is_none()
+        && options.language.is_none()
+        && !options.text_stats
+        && options.word_frequencies.is_none()
+        && matches!(options.encoding, Encoding::Auto | Encoding::Utf8)
+        && options.skip_bytes == 0
+        && options.limit_bytes.is_none()
+        && options.from_line.is_none()
+        && options.to_line.is_none()
+}
+
+// UTF-16 code units can't be split at an arbitrary byte, so a file starting with a UTF-16 byte
+// order mark is counted on a single thread.
+fn is_utf16(filepath: &Path, options: &Options) -> io::Result<bool> {
+    if options.encoding != Encoding::Auto {
+        return Ok(false);
+    }
+
+    let mut bytes = Vec::with_capacity(2);
+    File::open(filepath)?.take(2).read_to_end(&mut bytes)?;
+    Ok(encoding::sniff(&bytes).is_some())
+}
+
+fn process_chunks(filepath: &Path, chunk_count: u64, options: &Options) -> Result<Counts, Error> {
+    let chunks = split_file(filepath, chunk_count)?;
+
+    let partial_counts = thread::scope(|scope| {
+        let handles: Vec<_> = chunks
+            .iter()
+            .map(|chunk| scope.spawn(move || count_chunk(filepath, chunk, options)))
+            .collect();
+
+        handles
+            .into_iter()
+            .map(|handle| handle.join().map_err(|_| Error::ThreadPanicked)?.map_err(Error::from))
+            .collect::<Result<Vec<Counts>, Error>>()
+    })?;
+
+    Ok(merge(&chunks, partial_counts, options))
+}
+
+// Splits the file into roughly equal byte ranges. Each boundary is moved forward past any
+// UTF-8 continuation bytes so that no multi-byte character is cut in half.
+fn split_file(filepath: &Path, chunk_count: u64) -> io::Result<Vec<Chunk>> {
+    let mut file = File::open(filepath)?;
+    let file_length = file.metadata()?.len();
+
+    let mut boundaries: Vec<u64> = vec![0];
+    for i in 1..chunk_count {
+        let boundary = align_to_char_start(&mut file, file_length * i / chunk_count)?;
+        if boundary > *boundaries.last().unwrap() && boundary < file_length {
+            boundaries.push(boundary);
+        }
+    }
+    boundaries.push(file_length);
+
+    let mut chunks = Vec::new();
+    for window in boundaries.windows(2) {
+        let (start, end) = (window[0], window[1]);
+        let (previous_byte, first_byte) = if start == 0 {
+            (b' ', b' ')
+        } else {
+            let mut bytes = [0; 2];
+            file.seek(SeekFrom::Start(start - 1))?;
+            file.read_exact(&mut bytes)?;
+            (bytes[0], bytes[1])
+        };
+        chunks.push(Chunk { start, end, previous_byte, first_byte });
+    }
+
+    Ok(chunks)
+}
+
+fn align_to_char_start(file: &mut File, position: u64) -> io::Result<u64> {
+    let mut bytes = [0; 4];
+    file.seek(SeekFrom::Start(position))?;
+    let read_count = file.read(&mut bytes)?;
+
+    let continuation_bytes = bytes[0..read_count]
+        .iter()
+        .take_while(|&&byte| is_utf8_continuation(byte))
+        .count();
+
+    Ok(position + continuation_bytes as u64)
+}
+
+fn is_utf8_continuation(byte: u8) -> bool {
+    (byte & 0b1100_0000) == 0b1000_0000
+}
+
+fn count_chunk(filepath: &Path, chunk: &Chunk, options: &Options) -> io::Result<Counts> {
+    let mut file = File::open(filepath)?;
+    file.seek(SeekFrom::Start(chunk.start))?;
+    count_reader(file.take(chunk.end - chunk.start), options)
+}
+
+// Each chunk is counted as though it was preceded by whitespace. When a word runs across
+// a boundary it has been counted once in each chunk, so one is taken back off. Likewise a CRLF
+// pair cut in half by a boundary was counted as a CR in one chunk and an LF in the next.
+fn merge(chunks: &[Chunk], partial_counts: Vec<Counts>, options: &Options) -> Counts {
+    let mut total = Counts::default();
+    for (chunk, counts) in chunks.iter().zip(partial_counts) {
+        total += counts;
+        if chunk.start > 0 && !is_whitespace(chunk.previous_byte) && !is_whitespace(chunk.first_byte) {
+            total.word_count -= 1;
+        }
+        if chunk.start > 0 && options.counts_line_endings() && chunk.previous_byte == b'\r' && chunk.first_byte == b'\n' {
+            total.line_endings.cr -= 1;
+            total.line_endings.lf -= 1;
+            total.line_endings.crlf += 1;
+        }
+    }
+    if options.counts_line_endings() {
+        total.line_count = total.line_endings.line_count(options.line_terminator);
+    }
+    total
+}
+
+#[cfg(test)]
+mod parallel_tests {
+    use super::*;
+    use crate::wc::{process_file, LineEndings, LineTerminator};
+
+    const TEST_FILE: &str = "test_files/pg123.txt";
+
+    fn test_file() -> &'static Path {
+        Path::new(TEST_FILE)
+    }
+
+    #[test]
+    fn process_chunks_matches_sequential_counts() {
+        // Arrange
+        let expected = process_file(TEST_FILE.to_string()).unwrap();
+
+        for chunk_count in 1..=16 {
+            //Act
+            let counts = process_chunks(test_file(), chunk_count, &Options::default()).unwrap();
+
+            //Assert
+            assert_eq!(counts.byte_count, expected.byte_count, "{} chunks", chunk_count);
+            assert_eq!(counts.char_count, expected.char_count, "{} chunks", chunk_count);
+            assert_eq!(counts.word_count, expected.word_count, "{} chunks", chunk_count);
+            assert_eq!(counts.line_count, expected.line_count, "{} chunks", chunk_count);
+        }
+    }
+
+    #[test]
+    fn process_file_parallel_matches_sequential_counts() {
+        // Arrange
+        let expected = process_file(TEST_FILE.to_string()).unwrap();
+
+        //Act
+        let counts = process_file_parallel(test_file(), 4, &Options::default()).unwrap();
+
+        //Assert
+        assert_eq!(counts.byte_count, expected.byte_count);
+        assert_eq!(counts.char_count, expected.char_count);
+        assert_eq!(counts.word_count, expected.word_count);
+        assert_eq!(counts.line_count, expected.line_count);
+    }
+
+    #[test]
+    fn process_chunks_matches_sequential_line_endings() {
+        // Arrange
+        let options = Options { line_terminator: LineTerminator::Any, line_endings: true, ..Options::default() };
+        let expected = count_reader(File::open(TEST_FILE).unwrap(), &options).unwrap();
+
+        for chunk_count in 1..=16 {
+            //Act
+            let counts = process_chunks(test_file(), chunk_count, &options).unwrap();
+
+            //Assert
+            assert_eq!(counts.line_endings, expected.line_endings, "{} chunks", chunk_count);
+            assert_eq!(counts.line_count, expected.line_count, "{} chunks", chunk_count);
+        }
+    }
+
+    #[test]
+    fn process_chunks_matches_sequential_classes() {
+        // Arrange
+        let options = Options { classes: true, ..Options::default() };
+        let expected = count_reader(File::open(TEST_FILE).unwrap(), &options).unwrap();
+
+        for chunk_count in 1..=16 {
+            //Act
+            let counts = process_chunks(test_file(), chunk_count, &options).unwrap();
+
+            //Assert
+            assert_eq!(counts.classes, expected.classes, "{} chunks", chunk_count);
+        }
+    }
+
+    #[test]
+    fn split_file_never_cuts_a_multi_byte_character() {
+        // Arrange
+        let contents = std::fs::read(TEST_FILE).unwrap();
+
+        //Act
+        let chunks = split_file(test_file(), 64).unwrap();
+
+        //Assert
+        for chunk in chunks {
+            assert!(!is_utf8_continuation(contents[chunk.start as usize]));
+        }
+    }
+
+    #[test]
+    fn merge_removes_word_counted_in_both_chunks() {
+        // Arrange
+        let chunks = [
+            Chunk { start: 0, end: 3, previous_byte: b' ', first_byte: b' ' },
+            Chunk { start: 3, end: 5, previous_byte: b'l', first_byte: b'l' },
+        ];
+        let partial_counts = vec![
+            Counts { byte_count: 3, char_count: 3, word_count: 1, ..Counts::default() },
+            Counts { byte_count: 2, char_count: 2, word_count: 1, ..Counts::default() },
+        ];
+
+        //Act
+        let counts = merge(&chunks, partial_counts, &Options::default());
+
+        //Assert
+        assert_eq!(counts.byte_count, 5);
+        assert_eq!(counts.word_count, 1);
+    }
+
+    #[test]
+    fn merge_joins_crlf_pair_cut_by_a_boundary() {
+        // Arrange
+        let options = Options { line_terminator: LineTerminator::Cr, ..Options::default() };
+        let chunks = [
+            Chunk { start: 0, end: 3, previous_byte: b' ', first_byte: b'a' },
+            Chunk { start: 3, end: 5, previous_byte: b'\r', first_byte: b'\n' },
+        ];
+        let partial_counts = vec![
+            Counts { line_endings: LineEndings { lf: 0, crlf: 0, cr: 2 }, ..Counts::default() },
+            Counts { line_endings: LineEndings { lf: 1, crlf: 0, cr: 1 }, ..Counts::default() },
+        ];
+
+        //Act
+        let counts = merge(&chunks, partial_counts, &options);
+
+        //Assert
+        assert_eq!(counts.line_endings, LineEndings { lf: 0, crlf: 1, cr: 2 });
+        assert_eq!(counts.line_count, 3);
+    }
+}
@@ -0,0 +1,152 @@
+use super::line_ending::{LineEnding, LineEndingScanner, LineTerminator};
+
+// Picks out the lines from `from_line` to `to_line`, numbered from 1, so only they are counted.
+// Lines end with the terminator, which belongs to the line it ends. The line number is carried
+// from one buffer to the next, and once the last line has been passed nothing more needs reading.
+#[derive(Clone)]
+pub(super) struct LineSelector {
+    from_line: u64,
+    to_line: u64,
+    terminator: LineTerminator,
+    scanner: LineEndingScanner,
+    line: u64,
+}
+
+impl LineSelector {
+    pub(super) fn new(from_line: Option<u64>, to_line: Option<u64>, terminator: LineTerminator) -> Self {
+        LineSelector {
+            from_line: from_line.unwrap_or(1),
+            to_line: to_line.unwrap_or(u64::MAX),
+            terminator,
+            scanner: LineEndingScanner::default(),
+            line: 1,
+        }
+    }
+
+    pub(super) fn is_done(&self) -> bool {
+        self.line > self.to_line
+    }
+
+    // The part of the buffer that is in the selected lines. Since the lines are consecutive it is
+    // always a single slice, and is empty before the first line and after the last. A carriage
+    // return that ends a line on its own is only found at the byte after it, which may be the
+    // first byte of the next buffer.
+    pub(super) fn select<'a>(&mut self, buffer: &'a [u8]) -> &'a [u8] {
+        if self.is_done() {
+            return &[];
+        }
+        if self.line >= self.from_line && self.to_line == u64::MAX {
+            return buffer;
+        }
+
+        let mut start = 0;
+        for (index, &byte) in buffer.iter().enumerate() {
+            let end = match self.scanner.next(byte as char) {
+                Some(LineEnding::Cr) if self.terminator.ends_line(LineEnding::Cr) => index,
+                Some(ending) if self.terminator.ends_line(ending) => index + 1,
+                _ => continue,
+            };
+            self.line += 1;
+
+            if self.line == self.from_line {
+                start = end;
+                if self.to_line == u64::MAX {
+                    return &buffer[start..];
+                }
+            }
+            if self.is_done() {
+                return if self.line > self.from_line { &buffer[start..end] } else { &[] };
+            }
+        }
+
+        if self.line < self.from_line {
+            return &[];
+        }
+        &buffer[start..]
+    }
+}
+
+#[cfg(test)]
+mod line_selector_tests {
+    use super::*;
+
+    fn select(input: &[u8], from_line: Option<u64>, to_line: Option<u64>, piece_size: usize) -> Vec<u8> {
+        select_lines(input, from_line, to_line, LineTerminator::Lf, piece_size)
+    }
+
+    fn select_lines(input: &[u8], from_line: Option<u64>, to_line: Option<u64>, terminator: LineTerminator, piece_size: usize) -> Vec<u8> {
+        let mut selector = LineSelector::new(from_line, to_line, terminator);
+        let mut selected = Vec::new();
+        for piece in input.chunks(piece_size) {
+            selected.extend_from_slice(selector.select(piece));
+        }
+        selected
+    }
+
+    #[test]
+    fn selects_lines_split_across_buffers() {
+        // Arrange
+        let input = b"one\ntwo\nthree\nfour\nfive";
+
+        for piece_size in 1..=input.len() {
+            //Act
+            let middle = select(input, Some(2), Some(4), piece_size);
+            let tail = select(input, Some(4), None, piece_size);
+            let head = select(input, None, Some(1), piece_size);
+
+            //Assert
+            assert_eq!(middle, b"two\nthree\nfour\n", "piece size {}", piece_size);
+            assert_eq!(tail, b"four\nfive", "piece size {}", piece_size);
+            assert_eq!(head, b"one\n", "piece size {}", piece_size);
+        }
+    }
+
+    #[test]
+    fn selects_lines_ending_with_carriage_returns() {
+        // Arrange
+        let input = b"one\rtwo\r\nthree\rfour\nfive\r";
+
+        for piece_size in 1..=input.len() {
+            //Act
+            let middle = select_lines(input, Some(2), Some(3), LineTerminator::Cr, piece_size);
+            let tail = select_lines(input, Some(4), None, LineTerminator::Any, piece_size);
+            let head = select_lines(input, None, Some(2), LineTerminator::Lf, piece_size);
+
+            //Assert
+            assert_eq!(middle, b"two\r\nthree\r", "piece size {}", piece_size);
+            assert_eq!(tail, b"four\nfive\r", "piece size {}", piece_size);
+            assert_eq!(head, b"one\rtwo\r\nthree\rfour\n", "piece size {}", piece_size);
+        }
+    }
+
+    #[test]
+    fn lines_past_the_end_select_nothing() {
+        // Arrange
+        let input = b"one\ntwo\n";
+
+        //Act
+        let selected = select(input, Some(3), Some(10), 3);
+        let backwards = select(input, Some(2), Some(1), 3);
+
+        //Assert
+        assert!(selected.is_empty());
+        assert!(backwards.is_empty());
+    }
+
+    #[test]
+    fn is_done_after_the_last_line() {
+        // Arrange
+        let mut selector = LineSelector::new(Some(1), Some(2), LineTerminator::Lf);
+
+        //Act
+        let first = selector.select(b"one\ntw");
+        let done_after_first = selector.is_done();
+        let second = selector.select(b"o\nthree\n");
+
+        //Assert
+        assert_eq!(first, b"one\ntw");
+        assert!(!done_after_first);
+        assert_eq!(second, b"o\n");
+        assert!(selector.is_done());
+    }
+}
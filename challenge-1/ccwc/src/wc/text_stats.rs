@@ -0,0 +1,230 @@
+use std::ops::AddAssign;
+
+use super::{is_new_line, is_whitespace};
+
+// Words ending with a full stop that don't end a sentence. They are compared in lower case
+// without the full stop.
+const ABBREVIATIONS: &[&str] = &[
+    "mr", "mrs", "ms", "dr", "prof", "sr", "jr", "st", "mt", "vs", "etc", "no", "vol", "fig", "inc", "ltd", "co", "corp", "dept", "approx",
+];
+
+// Sentences, paragraphs and syllables, for readability scores. Words are found the same way as
+// for the word count.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TextStats {
+    pub words: u64,
+    pub sentences: u64,
+    pub paragraphs: u64,
+    pub syllables: u64,
+}
+
+impl TextStats {
+    // Flesch Reading Ease. Higher is easier, and most plain English scores between 60 and 70.
+    pub fn reading_ease(&self) -> f64 {
+        if self.words == 0 {
+            return 0.0;
+        }
+        206.835 - 1.015 * self.words_per_sentence() - 84.6 * self.syllables_per_word()
+    }
+
+    // Flesch-Kincaid grade level, the US school grade the text is written for.
+    pub fn grade_level(&self) -> f64 {
+        if self.words == 0 {
+            return 0.0;
+        }
+        0.39 * self.words_per_sentence() + 11.8 * self.syllables_per_word() - 15.59
+    }
+
+    fn words_per_sentence(&self) -> f64 {
+        self.words as f64 / self.sentences.max(1) as f64
+    }
+
+    fn syllables_per_word(&self) -> f64 {
+        self.syllables as f64 / self.words as f64
+    }
+}
+
+impl AddAssign for TextStats {
+    fn add_assign(&mut self, other: TextStats) {
+        self.words += other.words;
+        self.sentences += other.sentences;
+        self.paragraphs += other.paragraphs;
+        self.syllables += other.syllables;
+    }
+}
+
+// A sentence ends with a word ending in '.', '!' or '?', perhaps followed by closing quotes or
+// brackets, unless the word is an abbreviation or an initial. A paragraph is a run of lines that
+// aren't blank, and a blank line also ends any sentence left unfinished, such as a heading. The end
+// of a word cut in half by the end of a buffer is held back until the next buffer, the same as for
+// word frequencies.
+#[derive(Clone, Default)]
+pub(super) struct TextStatsCounter {
+    word: Vec<u8>,
+    words_in_sentence: u64,
+    in_paragraph: bool,
+    line_has_text: bool,
+    pub(super) text_stats: TextStats,
+}
+
+impl TextStatsCounter {
+    pub(super) fn feed(&mut self, buffer: &[u8]) {
+        for &byte in buffer {
+            if !is_whitespace(byte) {
+                self.word.push(byte);
+                if !self.in_paragraph {
+                    self.in_paragraph = true;
+                    self.text_stats.paragraphs += 1;
+                }
+                self.line_has_text = true;
+                continue;
+            }
+
+            if !self.word.is_empty() {
+                self.end_word();
+            }
+            if is_new_line(byte) {
+                if !self.line_has_text {
+                    self.end_paragraph();
+                }
+                self.line_has_text = false;
+            }
+        }
+    }
+
+    pub(super) fn finish(&mut self) {
+        if !self.word.is_empty() {
+            self.end_word();
+        }
+        self.end_paragraph();
+    }
+
+    fn end_word(&mut self) {
+        let word = std::mem::take(&mut self.word);
+        self.text_stats.words += 1;
+        self.text_stats.syllables += syllables(&word);
+        self.words_in_sentence += 1;
+        if ends_sentence(&word) {
+            self.text_stats.sentences += 1;
+            self.words_in_sentence = 0;
+        }
+        self.word = word;
+        self.word.clear();
+    }
+
+    fn end_paragraph(&mut self) {
+        self.in_paragraph = false;
+        if self.words_in_sentence > 0 {
+            self.text_stats.sentences += 1;
+            self.words_in_sentence = 0;
+        }
+    }
+}
+
+fn ends_sentence(word: &[u8]) -> bool {
+    let end = word.iter().rposition(|byte| !b"\"')]".contains(byte)).map_or(0, |index| index + 1);
+    let word = &word[..end];
+    match word.last() {
+        Some(b'!' | b'?') => true,
+        Some(b'.') => !is_abbreviation(&word[..word.len() - 1]),
+        _ => false,
+    }
+}
+
+// Initials such as "J.", words with full stops inside such as "e.g." and "U.S", and the common
+// abbreviations. An ellipsis is treated the same way, so it doesn't end a sentence.
+fn is_abbreviation(word: &[u8]) -> bool {
+    let word = word.strip_prefix(b"(").unwrap_or(word);
+    if word.contains(&b'.') || (word.len() == 1 && word[0].is_ascii_alphabetic()) {
+        return true;
+    }
+    let word = word.to_ascii_lowercase();
+    ABBREVIATIONS.iter().any(|abbreviation| abbreviation.as_bytes() == word)
+}
+
+// An estimate for English: each run of vowels is a syllable, except a silent 'e' at the end.
+// A word with letters always has at least one syllable, and one without, such as a number, has none.
+fn syllables(word: &[u8]) -> u64 {
+    let letters: Vec<u8> = word.iter().filter(|byte| byte.is_ascii_alphabetic()).map(|byte| byte.to_ascii_lowercase()).collect();
+    if letters.is_empty() {
+        return 0;
+    }
+
+    let is_vowel = |byte: u8| b"aeiouy".contains(&byte);
+    let mut count = 0;
+    let mut previous_vowel = false;
+    for &letter in &letters {
+        let vowel = is_vowel(letter);
+        if vowel && !previous_vowel {
+            count += 1;
+        }
+        previous_vowel = vowel;
+    }
+
+    let silent_e = match letters[..] {
+        [.., consonant, b'l', b'e'] => is_vowel(consonant),
+        [.., b'e'] => true,
+        _ => false,
+    };
+    if silent_e && count > 1 {
+        count -= 1;
+    }
+    count.max(1)
+}
+
+#[cfg(test)]
+mod text_stats_tests {
+    use super::*;
+
+    fn text_stats(input: &str, piece_size: usize) -> TextStats {
+        let mut counter = TextStatsCounter::default();
+        for piece in input.as_bytes().chunks(piece_size) {
+            counter.feed(piece);
+        }
+        counter.finish();
+        counter.text_stats
+    }
+
+    #[test]
+    fn sentences_and_paragraphs_split_across_buffers() {
+        // Arrange
+        let input = "A Heading\n\nMr. Smith met Dr. J. Jones at 9 a.m. today. Did he stay?\r\nHe said \"yes!\" (He did.)\n\n\n  Wait... the end";
+
+        for piece_size in 1..=input.len() {
+            //Act
+            let stats = text_stats(input, piece_size);
+
+            //Assert
+            assert_eq!(stats.words, 23, "piece size {}", piece_size);
+            assert_eq!(stats.sentences, 6, "piece size {}", piece_size);
+            assert_eq!(stats.paragraphs, 3, "piece size {}", piece_size);
+        }
+    }
+
+    #[test]
+    fn syllables_are_estimated_from_vowel_groups() {
+        // Arrange
+        let words = ["the", "make", "table", "syllable", "readability", "queue", "rhythm", "42", "Mr."];
+
+        //Act
+        let counts: Vec<u64> = words.iter().map(|word| syllables(word.as_bytes())).collect();
+
+        //Assert
+        assert_eq!(counts, vec![1, 1, 2, 3, 5, 1, 1, 0, 1]);
+    }
+
+    #[test]
+    fn flesch_scores() {
+        // Arrange
+        let stats = TextStats { words: 100, sentences: 5, paragraphs: 1, syllables: 150 };
+
+        //Act
+        let reading_ease = stats.reading_ease();
+        let grade_level = stats.grade_level();
+
+        //Assert
+        assert!((reading_ease - 59.635).abs() < 1e-9);
+        assert!((grade_level - 9.91).abs() < 1e-9);
+        assert_eq!(TextStats::default().reading_ease(), 0.0);
+    }
+}
@@ -0,0 +1,246 @@
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{self, BufRead, BufReader},
+    path::Path,
+    sync::Arc,
+};
+
+// A piece longer than this is split, since merging a piece takes time in the square of its length.
+const MAX_PIECE_LENGTH: usize = 256;
+// The token counts of pieces already seen are kept, since most words come up again and again.
+// The cache is emptied when it gets this big.
+const MAX_CACHED_PIECES: usize = 100_000;
+
+// A byte-pair encoding vocabulary in the tiktoken format used by OpenAI's tokenizers, such as
+// `cl100k_base.tiktoken`. Each line is a token, base64 encoded, and its rank. Tokens with a lower
+// rank were merged earlier when the vocabulary was trained.
+#[derive(Debug)]
+pub struct Vocabulary {
+    ranks: HashMap<Vec<u8>, u32>,
+}
+
+impl Vocabulary {
+    pub fn load(path: &Path) -> io::Result<Vocabulary> {
+        Vocabulary::from_reader(BufReader::new(File::open(path)?))
+    }
+
+    pub fn from_reader<R: BufRead>(reader: R) -> io::Result<Vocabulary> {
+        let mut ranks = HashMap::new();
+        for (index, line) in reader.lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let invalid = || io::Error::new(io::ErrorKind::InvalidData, format!("line {}: expected a base64 token and a rank", index + 1));
+            let (token, rank) = line.trim().split_once(' ').ok_or_else(invalid)?;
+            let token = decode_base64(token).ok_or_else(invalid)?;
+            let rank: u32 = rank.trim().parse().map_err(|_| invalid())?;
+            ranks.insert(token, rank);
+        }
+        Ok(Vocabulary { ranks })
+    }
+
+    // The number of tokens a piece encodes to. Starting from single bytes, the adjacent pair that
+    // makes the token with the lowest rank is merged until no pair makes a token. A byte that is
+    // not in the vocabulary is still one token.
+    fn count(&self, piece: &[u8]) -> u64 {
+        if piece.is_empty() {
+            return 0;
+        }
+        if self.ranks.contains_key(piece) {
+            return 1;
+        }
+
+        let mut starts: Vec<usize> = (0..piece.len()).collect();
+        loop {
+            let end = |part: usize| starts.get(part).copied().unwrap_or(piece.len());
+            let lowest = (0..starts.len() - 1)
+                .filter_map(|part| self.ranks.get(&piece[starts[part]..end(part + 2)]).map(|&rank| (rank, part)))
+                .min();
+            match lowest {
+                Some((_, part)) => {
+                    starts.remove(part + 1);
+                }
+                None => return starts.len() as u64,
+            }
+        }
+    }
+}
+
+fn decode_base64(text: &str) -> Option<Vec<u8>> {
+    let mut bytes = Vec::with_capacity(text.len() * 3 / 4);
+    let mut bits: u32 = 0;
+    let mut bit_count = 0;
+    for byte in text.trim_end_matches('=').bytes() {
+        let value = match byte {
+            b'A'..=b'Z' => byte - b'A',
+            b'a'..=b'z' => byte - b'a' + 26,
+            b'0'..=b'9' => byte - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            _ => return None,
+        };
+        bits = ((bits << 6) | value as u32) & 0xFFFF;
+        bit_count += 6;
+        if bit_count >= 8 {
+            bit_count -= 8;
+            bytes.push((bits >> bit_count) as u8);
+        }
+    }
+    Some(bytes)
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum ByteClass {
+    Letter,
+    Digit,
+    Space,
+    Other,
+}
+
+// Bytes of multi-byte UTF-8 characters are treated as letters.
+fn classify(byte: u8) -> ByteClass {
+    match byte {
+        b' ' | b'\t' | b'\r' | b'\n' | 0x0B | 0x0C => ByteClass::Space,
+        b'0'..=b'9' => ByteClass::Digit,
+        b'a'..=b'z' | b'A'..=b'Z' | 0x80.. => ByteClass::Letter,
+        _ => ByteClass::Other,
+    }
+}
+
+// Counts tokens the way GPT style tokenizers do, by first splitting the input into pieces and
+// then encoding each piece on its own. A piece is a run of letters, of up to three digits, of
+// other symbols, or of whitespace, and the last space before a word belongs to the word, as in
+// " the". This is close to, but simpler than, the pattern tiktoken splits with, so counts can be
+// a little different. The piece at the end of a buffer is held back until the next buffer.
+#[derive(Clone)]
+pub(super) struct TokenCounter {
+    vocabulary: Arc<Vocabulary>,
+    piece: Vec<u8>,
+    class: Option<ByteClass>,
+    // The number of bytes in the piece that are of its class, not counting a leading space.
+    run: usize,
+    cache: HashMap<Vec<u8>, u64>,
+    pub(super) token_count: u64,
+}
+
+impl TokenCounter {
+    pub(super) fn new(vocabulary: &Arc<Vocabulary>) -> Self {
+        TokenCounter {
+            vocabulary: Arc::clone(vocabulary),
+            piece: Vec::new(),
+            class: None,
+            run: 0,
+            cache: HashMap::new(),
+            token_count: 0,
+        }
+    }
+
+    pub(super) fn feed(&mut self, buffer: &[u8]) {
+        for &byte in buffer {
+            let class = classify(byte);
+            match self.class {
+                None => {}
+                // The last space of a run of whitespace joins the word that follows.
+                Some(ByteClass::Space) if class != ByteClass::Space && self.piece.last() == Some(&b' ') => {
+                    self.emit(self.piece.len() - 1);
+                    self.run = 0;
+                }
+                Some(current) if current == class && !(class == ByteClass::Digit && self.run == 3) && self.piece.len() < MAX_PIECE_LENGTH => {}
+                Some(_) => {
+                    self.emit(self.piece.len());
+                    self.run = 0;
+                }
+            }
+            self.piece.push(byte);
+            self.class = Some(class);
+            self.run += 1;
+        }
+    }
+
+    pub(super) fn finish(&mut self) {
+        self.emit(self.piece.len());
+        self.class = None;
+        self.run = 0;
+    }
+
+    // Counts the tokens in the first `end` bytes of the piece and removes them.
+    fn emit(&mut self, end: usize) {
+        if end == 0 {
+            return;
+        }
+        let piece = &self.piece[..end];
+        let count = match self.cache.get(piece) {
+            Some(&count) => count,
+            None => {
+                let count = self.vocabulary.count(piece);
+                if self.cache.len() >= MAX_CACHED_PIECES {
+                    self.cache.clear();
+                }
+                self.cache.insert(piece.to_vec(), count);
+                count
+            }
+        };
+        self.token_count += count;
+        self.piece.drain(..end);
+    }
+}
+
+#[cfg(test)]
+mod token_counter_tests {
+    use super::*;
+
+    const VOCABULARY: &str = "test_files/tokens/small.tiktoken";
+
+    fn count(input: &str, piece_size: usize) -> u64 {
+        let vocabulary = Arc::new(Vocabulary::load(Path::new(VOCABULARY)).unwrap());
+        let mut counter = TokenCounter::new(&vocabulary);
+        for piece in input.as_bytes().chunks(piece_size) {
+            counter.feed(piece);
+        }
+        counter.finish();
+        counter.token_count
+    }
+
+    #[test]
+    fn pieces_are_merged_into_tokens() {
+        // Arrange
+        let vocabulary = Vocabulary::load(Path::new(VOCABULARY)).unwrap();
+
+        //Act
+        let counts: Vec<u64> = [" the", " hello", "then", " mat", "llama", "\u{e9}"].iter().map(|piece| vocabulary.count(piece.as_bytes())).collect();
+
+        //Assert
+        assert_eq!(counts, vec![1, 1, 2, 3, 4, 2]);
+    }
+
+    #[test]
+    fn counts_are_the_same_for_any_buffer_size() {
+        // Arrange
+        let input = "the cat sat on the mat,\n\n  hello 12345!";
+
+        for piece_size in 1..=input.len() {
+            //Act
+            let tokens = count(input, piece_size);
+
+            //Assert
+            // "the", " cat", " sat", " on", " the", " mat", ",", "\n\n ", " hello", " 123", "45", "!"
+            assert_eq!(tokens, 1 + 1 + 1 + 1 + 1 + 3 + 1 + 3 + 1 + 4 + 2 + 1, "piece size {}", piece_size);
+        }
+    }
+
+    #[test]
+    fn invalid_vocabulary_lines_are_reported() {
+        // Arrange
+        let contents = "dGhl 0\n\nbm90IGJhc2U2NA 1\n!!! 2\n";
+
+        //Act
+        let error = Vocabulary::from_reader(contents.as_bytes()).unwrap_err();
+
+        //Assert
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(error.to_string(), "line 4: expected a base64 token and a rank");
+    }
+}
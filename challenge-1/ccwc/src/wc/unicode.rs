@@ -0,0 +1,404 @@
+use std::str;
+
+use unicode_segmentation::UnicodeSegmentation;
+
+use super::TextMode;
+
+const REPLACEMENT_CHARACTER: char = '\u{FFFD}';
+
+enum Decoded {
+    Char(char, usize),
+    Invalid(usize),
+    Incomplete,
+}
+
+// Decodes UTF-8 a buffer at a time. A multi-byte character cut in half by the end of a buffer is
+// held back until the next buffer completes it. Invalid sequences decode to U+FFFD, the same as
+// `String::from_utf8_lossy`.
+#[derive(Clone, Default)]
+pub(super) struct Utf8Decoder {
+    pending: Vec<u8>,
+}
+
+impl Utf8Decoder {
+    pub(super) fn decode(&mut self, buffer: &[u8], emit: &mut impl FnMut(char)) {
+        let mut input = buffer;
+
+        if !self.pending.is_empty() {
+            let take = input.len().min(4 - self.pending.len());
+            let mut joined = self.pending.clone();
+            joined.extend_from_slice(&input[..take]);
+
+            match decode_first(&joined) {
+                Decoded::Incomplete => {
+                    self.pending = joined;
+                    return;
+                }
+                Decoded::Char(char, length) => {
+                    emit(char);
+                    input = &input[length - self.pending.len()..];
+                }
+                Decoded::Invalid(length) => {
+                    emit(REPLACEMENT_CHARACTER);
+                    input = &input[length.saturating_sub(self.pending.len())..];
+                }
+            }
+            self.pending.clear();
+        }
+
+        loop {
+            match str::from_utf8(input) {
+                Ok(text) => {
+                    text.chars().for_each(&mut *emit);
+                    return;
+                }
+                Err(error) => {
+                    let (valid, rest) = input.split_at(error.valid_up_to());
+                    str::from_utf8(valid).unwrap().chars().for_each(&mut *emit);
+
+                    match error.error_len() {
+                        Some(length) => {
+                            emit(REPLACEMENT_CHARACTER);
+                            input = &rest[length..];
+                        }
+                        None => {
+                            self.pending = rest.to_vec();
+                            return;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    // A sequence still incomplete at the end of the input is invalid.
+    pub(super) fn finish(&mut self, emit: &mut impl FnMut(char)) {
+        if !self.pending.is_empty() {
+            self.pending.clear();
+            emit(REPLACEMENT_CHARACTER);
+        }
+    }
+}
+
+fn decode_first(bytes: &[u8]) -> Decoded {
+    match str::from_utf8(bytes) {
+        Ok(text) => first_char(text),
+        Err(error) if error.valid_up_to() > 0 => first_char(str::from_utf8(&bytes[..error.valid_up_to()]).unwrap()),
+        Err(error) => match error.error_len() {
+            Some(length) => Decoded::Invalid(length),
+            None => Decoded::Incomplete,
+        },
+    }
+}
+
+fn first_char(text: &str) -> Decoded {
+    let char = text.chars().next().unwrap();
+    Decoded::Char(char, char.len_utf8())
+}
+
+// Counts characters and words from decoded text. With `TextMode::Unicode` a word is a run of
+// characters between any of the Unicode `White_Space` code points. With `TextMode::Uax29` the
+// text is split with the UAX #29 word boundary rules and each segment containing a letter or
+// digit is a word, so each ideograph in Chinese or Japanese text counts as a word.
+#[derive(Clone)]
+pub(super) struct UnicodeCounter {
+    mode: TextMode,
+    decoder: Utf8Decoder,
+    previous_whitespace: bool,
+    // Text not yet segmented. Whether there is a boundary between the last two segments can
+    // depend on the text that follows, as in "can'" before "t", so both are held back and
+    // segmented again with the next buffer.
+    unsegmented: String,
+    pub(super) char_count: u64,
+    pub(super) word_count: u64,
+}
+
+impl UnicodeCounter {
+    pub(super) fn new(mode: TextMode) -> Self {
+        UnicodeCounter {
+            mode,
+            decoder: Utf8Decoder::default(),
+            previous_whitespace: true,
+            unsegmented: String::new(),
+            char_count: 0,
+            word_count: 0,
+        }
+    }
+
+    pub(super) fn feed(&mut self, buffer: &[u8]) {
+        let mut decoder = std::mem::take(&mut self.decoder);
+        decoder.decode(buffer, &mut |char| self.count_char(char));
+        self.decoder = decoder;
+
+        if self.mode == TextMode::Uax29 {
+            let mut count: u64 = 0;
+            let mut starts: (usize, usize) = (0, 0);
+            for (start, _) in self.unsegmented.split_word_bound_indices() {
+                count += 1;
+                starts = (starts.1, start);
+            }
+
+            if count > 2 {
+                self.word_count += count_segmented_words(&self.unsegmented[..starts.0]);
+                self.unsegmented.drain(..starts.0);
+            }
+        }
+    }
+
+    pub(super) fn finish(&mut self) {
+        let mut decoder = std::mem::take(&mut self.decoder);
+        decoder.finish(&mut |char| self.count_char(char));
+
+        if self.mode == TextMode::Uax29 {
+            self.word_count += count_segmented_words(&self.unsegmented);
+            self.unsegmented.clear();
+        }
+    }
+
+    fn count_char(&mut self, char: char) {
+        self.char_count += 1;
+
+        match self.mode {
+            TextMode::Uax29 => self.unsegmented.push(char),
+            _ => {
+                let whitespace = char.is_whitespace();
+                if self.previous_whitespace && !whitespace {
+                    self.word_count += 1;
+                }
+                self.previous_whitespace = whitespace;
+            }
+        }
+    }
+}
+
+fn count_segmented_words(text: &str) -> u64 {
+    text.unicode_words().count() as u64
+}
+
+// Counts extended grapheme clusters, the characters a user sees, so an emoji ZWJ sequence, a flag
+// or a letter followed by combining marks each count once. The last cluster seen so far may still
+// be extended by the next buffer, so it is held back and segmented again with the text that follows.
+#[derive(Clone, Default)]
+pub(super) struct GraphemeCounter {
+    decoder: Utf8Decoder,
+    unsegmented: String,
+    pub(super) grapheme_count: u64,
+}
+
+impl GraphemeCounter {
+    pub(super) fn feed(&mut self, buffer: &[u8]) {
+        let unsegmented = &mut self.unsegmented;
+        self.decoder.decode(buffer, &mut |char| unsegmented.push(char));
+
+        let mut count: u64 = 0;
+        let mut last_start: usize = 0;
+        for (start, _) in self.unsegmented.grapheme_indices(true) {
+            count += 1;
+            last_start = start;
+        }
+
+        if count > 1 {
+            self.grapheme_count += count - 1;
+            self.unsegmented.drain(..last_start);
+        }
+    }
+
+    pub(super) fn finish(&mut self) {
+        let unsegmented = &mut self.unsegmented;
+        self.decoder.finish(&mut |char| unsegmented.push(char));
+
+        self.grapheme_count += self.unsegmented.graphemes(true).count() as u64;
+        self.unsegmented.clear();
+    }
+}
+
+#[cfg(test)]
+mod utf8_decoder_tests {
+    use super::*;
+
+    fn decode_in_pieces(input: &[u8], piece_size: usize) -> String {
+        let mut decoder = Utf8Decoder::default();
+        let mut output = String::new();
+        for piece in input.chunks(piece_size) {
+            decoder.decode(piece, &mut |char| output.push(char));
+        }
+        decoder.finish(&mut |char| output.push(char));
+        output
+    }
+
+    #[test]
+    fn decode_characters_split_across_buffers() {
+        // Arrange
+        let input = "h\u{e9}llo \u{4e16}\u{754c} \u{1f600}".as_bytes();
+
+        for piece_size in 1..=input.len() {
+            //Act
+            let output = decode_in_pieces(input, piece_size);
+
+            //Assert
+            assert_eq!(output, "h\u{e9}llo \u{4e16}\u{754c} \u{1f600}", "piece size {}", piece_size);
+        }
+    }
+
+    #[test]
+    fn decode_invalid_sequences_as_replacement_characters() {
+        // Arrange
+        let input = b"a\xE2\x41b\xFFc\xF0\x9F\x98";
+
+        for piece_size in 1..=input.len() {
+            //Act
+            let output = decode_in_pieces(input, piece_size);
+
+            //Assert
+            assert_eq!(output, String::from_utf8_lossy(input), "piece size {}", piece_size);
+        }
+    }
+}
+
+#[cfg(test)]
+mod unicode_counter_tests {
+    use super::*;
+
+    fn count(input: &str, mode: TextMode) -> (u64, u64) {
+        let mut counter = UnicodeCounter::new(mode);
+        for piece in input.as_bytes().chunks(3) {
+            counter.feed(piece);
+        }
+        counter.finish();
+        (counter.char_count, counter.word_count)
+    }
+
+    #[test]
+    fn unicode_whitespace_separates_words() {
+        // Arrange
+        let input = "one\u{a0}two\u{3000}three\u{2003}four";
+
+        //Act
+        let (char_count, word_count) = count(input, TextMode::Unicode);
+
+        //Assert
+        assert_eq!(char_count, 18);
+        assert_eq!(word_count, 4);
+    }
+
+    #[test]
+    fn uax29_counts_each_ideograph_as_a_word() {
+        // Arrange
+        let input = "\u{4f60}\u{597d}\u{4e16}\u{754c} hello, world";
+
+        //Act
+        let (char_count, word_count) = count(input, TextMode::Uax29);
+
+        //Assert
+        assert_eq!(char_count, 17);
+        assert_eq!(word_count, 6);
+    }
+
+    #[test]
+    fn uax29_keeps_contractions_and_numbers_together() {
+        // Arrange
+        let input = "can't stop 3.14 \u{2014} ok";
+
+        //Act
+        let (_, word_count) = count(input, TextMode::Uax29);
+
+        //Assert
+        assert_eq!(word_count, 4);
+    }
+
+    #[test]
+    fn uax29_joins_words_split_across_buffers() {
+        // Arrange
+        let input = "can't 3.14 e.g.\u{4f60}\u{597d}\u{4e16}\u{754c}\u{1f1ee}\u{1f1ea}x";
+
+        for piece_size in 1..=input.len() {
+            //Act
+            let mut counter = UnicodeCounter::new(TextMode::Uax29);
+            for piece in input.as_bytes().chunks(piece_size) {
+                counter.feed(piece);
+            }
+            counter.finish();
+
+            //Assert
+            assert_eq!(counter.word_count, 8, "piece size {}", piece_size);
+        }
+    }
+
+    #[test]
+    fn uax29_holds_back_only_the_last_segments() {
+        // Arrange
+        let mut counter = UnicodeCounter::new(TextMode::Uax29);
+
+        //Act
+        for _ in 0..1000 {
+            counter.feed("\u{4f60}\u{597d}\u{4e16}\u{754c}".as_bytes());
+        }
+
+        //Assert
+        assert!(counter.unsegmented.chars().count() <= 2);
+        assert_eq!(counter.word_count, 3998);
+    }
+
+    #[test]
+    fn uax29_keeps_marks_after_whitespace_with_the_whitespace() {
+        // Arrange
+        // A vowel sign, a combining accent and a zero width joiner each follow a space.
+        let input = "x \u{93f}y a \u{301}b \u{200d}\u{1f600}c\u{3000}\u{ad}d";
+        let mut whole = UnicodeCounter::new(TextMode::Uax29);
+        whole.feed(input.as_bytes());
+        whole.finish();
+
+        for piece_size in 1..=input.len() {
+            //Act
+            let mut counter = UnicodeCounter::new(TextMode::Uax29);
+            for piece in input.as_bytes().chunks(piece_size) {
+                counter.feed(piece);
+            }
+            counter.finish();
+
+            //Assert
+            assert_eq!(counter.word_count, whole.word_count, "piece size {}", piece_size);
+        }
+    }
+}
+
+#[cfg(test)]
+mod grapheme_counter_tests {
+    use super::*;
+
+    fn count(input: &str, piece_size: usize) -> u64 {
+        let mut counter = GraphemeCounter::default();
+        for piece in input.as_bytes().chunks(piece_size) {
+            counter.feed(piece);
+        }
+        counter.finish();
+        counter.grapheme_count
+    }
+
+    #[test]
+    fn combining_marks_count_with_their_base_character() {
+        // Arrange
+        let input = "e\u{301}a\u{308}\u{323}o";
+
+        //Act
+        let grapheme_count = count(input, 1);
+
+        //Assert
+        assert_eq!(grapheme_count, 3);
+    }
+
+    #[test]
+    fn emoji_sequences_count_once_when_split_across_buffers() {
+        // Arrange
+        // family (man, woman, girl joined with ZWJ), two flags, thumbs up with a skin tone, CRLF
+        let input = "\u{1f468}\u{200d}\u{1f469}\u{200d}\u{1f467}\u{1f1ee}\u{1f1ea}\u{1f1ef}\u{1f1f5}\u{1f44d}\u{1f3fd}\r\n";
+
+        for piece_size in 1..=input.len() {
+            //Act
+            let grapheme_count = count(input, piece_size);
+
+            //Assert
+            assert_eq!(grapheme_count, 5, "piece size {}", piece_size);
+        }
+    }
+}
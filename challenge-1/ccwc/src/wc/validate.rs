@@ -0,0 +1,211 @@
+use std::{fmt, str};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Utf8ErrorKind {
+    // A continuation byte (0x80 to 0xBF) without a lead byte before it.
+    UnexpectedContinuation,
+    // A lead byte that is not followed by enough continuation bytes.
+    Truncated,
+    // A character encoded with more bytes than it needs, such as 0xC0 0xAF for '/'.
+    Overlong,
+    // An encoded UTF-16 surrogate, U+D800 to U+DFFF.
+    Surrogate,
+    // A code point above U+10FFFF.
+    OutOfRange,
+    // 0xF5 to 0xFF, which never appear in UTF-8.
+    InvalidByte,
+}
+
+impl fmt::Display for Utf8ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let description = match self {
+            Utf8ErrorKind::UnexpectedContinuation => "unexpected continuation byte",
+            Utf8ErrorKind::Truncated => "truncated sequence",
+            Utf8ErrorKind::Overlong => "overlong encoding",
+            Utf8ErrorKind::Surrogate => "encoded surrogate",
+            Utf8ErrorKind::OutOfRange => "code point above U+10FFFF",
+            Utf8ErrorKind::InvalidByte => "invalid byte",
+        };
+        write!(f, "{}", description)
+    }
+}
+
+// Where an invalid sequence starts. `offset` counts bytes from 0 and `line` counts lines from 1.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Utf8Problem {
+    pub offset: u64,
+    pub line: u64,
+    pub kind: Utf8ErrorKind,
+}
+
+// Checks that the input is well-formed UTF-8, counting every invalid sequence and keeping the
+// position of the first `max_problems`. Invalid sequences are split the same way as
+// `String::from_utf8_lossy`, so the count matches the number of replacement characters it produces.
+#[derive(Clone)]
+pub(super) struct Utf8Validator {
+    max_problems: usize,
+    // The bytes of a sequence cut in half by the end of a buffer. `offset` and `line` are those of its first byte.
+    pending: Vec<u8>,
+    offset: u64,
+    line: u64,
+    pub(super) invalid_count: u64,
+    pub(super) problems: Vec<Utf8Problem>,
+}
+
+impl Utf8Validator {
+    pub(super) fn new(max_problems: usize) -> Self {
+        Utf8Validator {
+            max_problems,
+            pending: Vec::new(),
+            offset: 0,
+            line: 1,
+            invalid_count: 0,
+            problems: Vec::new(),
+        }
+    }
+
+    pub(super) fn feed(&mut self, buffer: &[u8]) {
+        if self.pending.is_empty() {
+            self.validate(buffer);
+            return;
+        }
+
+        let mut joined = std::mem::take(&mut self.pending);
+        joined.extend_from_slice(buffer);
+        self.validate(&joined);
+    }
+
+    // A sequence still incomplete at the end of the input is invalid.
+    pub(super) fn finish(&mut self) {
+        if !self.pending.is_empty() {
+            self.pending.clear();
+            self.record(Utf8ErrorKind::Truncated);
+        }
+    }
+
+    // `offset` and `line` are kept at the start of `input` as it is consumed.
+    fn validate(&mut self, mut input: &[u8]) {
+        loop {
+            match str::from_utf8(input) {
+                Ok(_) => {
+                    self.advance(input);
+                    return;
+                }
+                Err(error) => {
+                    let (valid, rest) = input.split_at(error.valid_up_to());
+                    self.advance(valid);
+
+                    match error.error_len() {
+                        Some(length) => {
+                            self.record(classify(rest));
+                            self.advance(&rest[..length]);
+                            input = &rest[length..];
+                        }
+                        None => {
+                            self.pending = rest.to_vec();
+                            return;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn advance(&mut self, bytes: &[u8]) {
+        self.offset += bytes.len() as u64;
+        self.line += bytes.iter().filter(|&&byte| byte == b'\n').count() as u64;
+    }
+
+    fn record(&mut self, kind: Utf8ErrorKind) {
+        self.invalid_count += 1;
+        if self.problems.len() < self.max_problems {
+            self.problems.push(Utf8Problem { offset: self.offset, line: self.line, kind });
+        }
+    }
+}
+
+// `bytes` starts with the rejected sequence. Its second byte tells an overlong encoding, a
+// surrogate or a code point that is too large apart from a sequence that just stops early.
+fn classify(bytes: &[u8]) -> Utf8ErrorKind {
+    let lead = bytes[0];
+    let second = bytes.get(1).copied().unwrap_or(0);
+
+    match lead {
+        0x80..=0xBF => Utf8ErrorKind::UnexpectedContinuation,
+        0xC0 | 0xC1 => Utf8ErrorKind::Overlong,
+        0xE0 if (0x80..0xA0).contains(&second) => Utf8ErrorKind::Overlong,
+        0xF0 if (0x80..0x90).contains(&second) => Utf8ErrorKind::Overlong,
+        0xED if (0xA0..=0xBF).contains(&second) => Utf8ErrorKind::Surrogate,
+        0xF4 if (0x90..=0xBF).contains(&second) => Utf8ErrorKind::OutOfRange,
+        0xF5..=0xFF => Utf8ErrorKind::InvalidByte,
+        _ => Utf8ErrorKind::Truncated,
+    }
+}
+
+#[cfg(test)]
+mod utf8_validator_tests {
+    use super::*;
+
+    fn validate_in_pieces(input: &[u8], piece_size: usize, max_problems: usize) -> Utf8Validator {
+        let mut validator = Utf8Validator::new(max_problems);
+        for piece in input.chunks(piece_size) {
+            validator.feed(piece);
+        }
+        validator.finish();
+        validator
+    }
+
+    #[test]
+    fn valid_input_has_no_problems() {
+        // Arrange
+        let input = "h\u{e9}llo\n\u{4e16}\u{754c} \u{1f600}\n".as_bytes();
+
+        for piece_size in 1..=input.len() {
+            //Act
+            let validator = validate_in_pieces(input, piece_size, 10);
+
+            //Assert
+            assert_eq!(validator.invalid_count, 0, "piece size {}", piece_size);
+            assert!(validator.problems.is_empty());
+        }
+    }
+
+    #[test]
+    fn problems_have_offset_line_and_kind() {
+        // Arrange
+        let input = b"ok\n\xC0\xAF\nx\xE2\x41 \xED\xA0\x80\n\x80\xF5\xF4\x90\x80\x80\xF0\x9F\x98";
+
+        for piece_size in 1..=input.len() {
+            //Act
+            let validator = validate_in_pieces(input, piece_size, 20);
+            let problems: Vec<(u64, u64, Utf8ErrorKind)> =
+                validator.problems.iter().map(|problem| (problem.offset, problem.line, problem.kind)).collect();
+
+            //Assert
+            assert_eq!(validator.invalid_count, String::from_utf8_lossy(input).matches('\u{FFFD}').count() as u64);
+            assert_eq!(&problems[..6], &[
+                (3, 2, Utf8ErrorKind::Overlong),
+                (4, 2, Utf8ErrorKind::UnexpectedContinuation),
+                (7, 3, Utf8ErrorKind::Truncated),
+                (10, 3, Utf8ErrorKind::Surrogate),
+                (11, 3, Utf8ErrorKind::UnexpectedContinuation),
+                (12, 3, Utf8ErrorKind::UnexpectedContinuation),
+            ], "piece size {}", piece_size);
+            assert_eq!(problems.last(), Some(&(20, 4, Utf8ErrorKind::Truncated)), "piece size {}", piece_size);
+        }
+    }
+
+    #[test]
+    fn only_the_first_problems_are_kept() {
+        // Arrange
+        let input = b"\xFF\xFF\xFF\xFF\xFF";
+
+        //Act
+        let validator = validate_in_pieces(input, 2, 3);
+
+        //Assert
+        assert_eq!(validator.invalid_count, 5);
+        assert_eq!(validator.problems.len(), 3);
+        assert_eq!(validator.problems[2].offset, 2);
+    }
+}
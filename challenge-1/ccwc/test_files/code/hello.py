@@ -0,0 +1,11 @@
+#!/usr/bin/env python3
+# Prints a greeting.
+import os
+
+
+def main():
+    name = os.environ.get("NAME", "world")  # defaults to world
+    print(f"hello, {name} #1")
+
+
+main()
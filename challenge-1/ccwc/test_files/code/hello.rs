@@ -0,0 +1,8 @@
+// Prints a greeting.
+fn main() {
+    /* The greeting can be changed
+       with an environment variable. */
+    let name = std::env::var("NAME").unwrap_or("world".to_string());
+
+    println!("hello, {}", name); // "/*" in a string is not a comment
+}
@@ -0,0 +1,5 @@
+#!/bin/sh
+# Prints a greeting and the number of arguments.
+
+echo "hello, ${NAME:-world}"
+echo "$# arguments"
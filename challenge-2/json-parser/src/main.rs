@@ -1,16 +1,17 @@
-mod parser;
-
 use std::{
     fs::{self},
     io::{self, Read},
+    process,
 };
 
-use clap::{Arg, ArgAction, Command};
-use parser::{lexer::Lexer, syntax_analyser::SyntaxAnalyzer, types::Token};
+use clap::{Arg, ArgAction, ArgGroup, Command};
+use json_parser::parser::{diagnostic::Diagnostic, lexer::Lexer, serializer::Serializer, syntax_analyser::SyntaxAnalyzer, types::Token};
 
 struct Arguments<'a> {
     filepath: Option<&'a String>,
     lexer_output: bool,
+    // None to only validate the input.
+    serializer: Option<Serializer>,
 }
 
 fn cli() -> Command {
@@ -23,6 +24,37 @@ fn cli() -> Command {
                 .action(ArgAction::SetTrue)
                 .help("Toggle lexer output"),
         )
+        .arg(
+            Arg::new("pretty")
+                .long("pretty")
+                .action(ArgAction::SetTrue)
+                .conflicts_with("minify")
+                .help("Write the parsed json pretty printed, with each array item and object member on its own line"),
+        )
+        .arg(
+            Arg::new("minify")
+                .long("minify")
+                .action(ArgAction::SetTrue)
+                .help("Write the parsed json on one line without whitespace"),
+        )
+        .arg(
+            Arg::new("indent")
+                .long("indent")
+                .value_name("N")
+                .default_value("2")
+                .value_parser(clap::value_parser!(usize))
+                .requires("pretty")
+                .conflicts_with("minify")
+                .help("With --pretty, the number of spaces each level of nesting is indented by"),
+        )
+        .arg(
+            Arg::new("sort_keys")
+                .long("sort-keys")
+                .action(ArgAction::SetTrue)
+                .requires("output")
+                .help("With --pretty or --minify, write object members sorted by key"),
+        )
+        .group(ArgGroup::new("output").args(["pretty", "minify"]))
         .arg(Arg::new("filepath"))
 }
 
@@ -50,11 +82,11 @@ fn main() {
 
     let lexer = Lexer::new();
 
+    let name = args.filepath.map_or("<stdin>", |filepath| filepath.as_str());
     let get_tokens_result = lexer.get_tokens(input.as_str());
 
     if get_tokens_result.is_err() {
-        println!("{}", get_tokens_result.unwrap_err());
-        return;
+        report_error(Diagnostic::from(&get_tokens_result.unwrap_err()), &input, name);
     }
 
     let tokens = get_tokens_result.unwrap();
@@ -67,25 +99,42 @@ fn main() {
     let parse_result = syntax_analyser.parse(tokens.into());
 
     match parse_result {
-        Ok(_) => println!("ok"),
-        Err(error) => println!("{}", error),
+        Ok(value) => match args.serializer {
+            Some(serializer) => println!("{}", serializer.serialize(&value)),
+            None => println!("ok"),
+        },
+        Err(error) => report_error(Diagnostic::from(&error), &input, name),
     }
 }
 
+fn report_error(diagnostic: Diagnostic, input: &str, name: &str) -> ! {
+    eprint!("{}", diagnostic.render(input, name));
+    process::exit(1);
+}
+
 fn print_tokens(tokens: Vec<Token>) {
     for token in tokens.clone() {
         println!("{}", token);
     }
 }
 
-fn parse_args(matches: &clap::ArgMatches) -> Arguments {
+fn parse_args(matches: &clap::ArgMatches) -> Arguments<'_> {
     let filepath = matches
         .try_get_one::<String>("filepath")
         .unwrap_or_default();
     let lexer_output: bool = matches.get_flag("lexer_output");
+    let sort_keys = matches.get_flag("sort_keys");
+    let serializer = if matches.get_flag("pretty") {
+        Some(Serializer { sort_keys, ..Serializer::pretty(*matches.get_one::<usize>("indent").unwrap()) })
+    } else if matches.get_flag("minify") {
+        Some(Serializer { sort_keys, ..Serializer::compact() })
+    } else {
+        None
+    };
 
     return Arguments {
         filepath: filepath.clone(),
         lexer_output: lexer_output,
+        serializer: serializer,
     };
 }
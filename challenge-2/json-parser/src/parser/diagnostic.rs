@@ -0,0 +1,124 @@
+use super::{syntax_analyser::SyntaxError, types::ScanError};
+
+// An error with its place in the source, ready to be shown the way rustc shows errors: the
+// message, the file, line and column, and the line with a caret under the problem.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub message: String,
+    pub line: usize,
+    // Counting from 1, in characters.
+    pub column: usize,
+    // The number of characters to underline. At least one caret is always drawn.
+    pub width: usize,
+}
+
+impl From<&ScanError> for Diagnostic {
+    fn from(error: &ScanError) -> Diagnostic {
+        Diagnostic {
+            message: lowercase_first(&error.message),
+            line: error.line,
+            column: error.column,
+            width: error.lexeme.chars().count(),
+        }
+    }
+}
+
+impl From<&SyntaxError> for Diagnostic {
+    fn from(error: &SyntaxError) -> Diagnostic {
+        Diagnostic {
+            message: error.message(),
+            line: error.line,
+            column: error.column,
+            width: error.token.as_ref().map_or(0, |token| token.lexeme.chars().count()),
+        }
+    }
+}
+
+// Scan error messages start with a capital, unlike rustc's.
+fn lowercase_first(message: &str) -> String {
+    let mut chars = message.chars();
+    match chars.next() {
+        Some(first) => first.to_lowercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+impl Diagnostic {
+    // `name` is the file the source was read from.
+    pub fn render(&self, source: &str, name: &str) -> String {
+        let line_number = self.line.to_string();
+        let gutter = " ".repeat(line_number.len());
+        let text = source.lines().nth(self.line.saturating_sub(1)).unwrap_or_default();
+        // Tabs are kept so the caret lines up however wide the terminal shows them.
+        let padding: String = text
+            .chars()
+            .take(self.column.saturating_sub(1))
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect();
+
+        format!(
+            "error: {}\n{}--> {}:{}:{}\n{} |\n{} | {}\n{} | {}{}\n",
+            self.message,
+            gutter,
+            name,
+            self.line,
+            self.column,
+            gutter,
+            line_number,
+            text,
+            gutter,
+            padding,
+            "^".repeat(self.width.max(1))
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::{lexer::Lexer, syntax_analyser::SyntaxAnalyzer};
+
+    fn render(source: &str) -> String {
+        let diagnostic = match Lexer::new().get_tokens(source) {
+            Ok(tokens) => Diagnostic::from(&SyntaxAnalyzer::new().parse(tokens).unwrap_err()),
+            Err(error) => Diagnostic::from(&error),
+        };
+        diagnostic.render(source, "test.json")
+    }
+
+    #[test]
+    fn test_render_syntax_error() {
+        // Arrange
+        let source = "{\n  \"café\": [1, 2,\n  \"b\" \"c\"]\n}";
+
+        // Act
+        let output = render(source);
+
+        // Assert
+        assert_eq!(output, "error: expected `,` or `]`, found `\"c\"`\n --> test.json:3:7\n  |\n3 |   \"b\" \"c\"]\n  |       ^^^\n");
+    }
+
+    #[test]
+    fn test_render_unexpected_end() {
+        // Arrange
+        let source = "{\"é\": [true";
+
+        // Act
+        let output = render(source);
+
+        // Assert
+        assert_eq!(output, "error: expected `,` or `]`, found end of input\n --> test.json:1:12\n  |\n1 | {\"é\": [true\n  |            ^\n");
+    }
+
+    #[test]
+    fn test_render_scan_error() {
+        // Arrange
+        let source = "[\n\t1,\n\ttrue,\n\ttrut\n]";
+
+        // Act
+        let output = render(source);
+
+        // Assert
+        assert_eq!(output, "error: expected 'e' at index 3 of keyword\n --> test.json:4:2\n  |\n4 | \ttrut\n  | \t^^^^\n");
+    }
+}
@@ -205,6 +205,25 @@ mod tests {
         assert!(tokens[2].token_type == TokenType::RightBracket);
     }
 
+    #[test]
+    fn get_tokens_non_ascii_string() {
+        // Arrange
+        let source = "[\"caf\u{e9} \u{1F600}\", 1]";
+        let lexer = Lexer::new();
+
+        // Act
+        let result = lexer.get_tokens(source);
+
+        // Assert
+        assert!(!result.is_err());
+        let tokens = result.unwrap();
+        assert!(tokens.len() == 5);
+        assert!(tokens[1].lexeme == "\"caf\u{e9} \u{1F600}\"");
+        assert!(tokens[1].position_start == 1 && tokens[1].position_end == 13);
+        assert!(tokens[3].lexeme == "1");
+        assert!(tokens[3].column == 12);
+    }
+
     #[test]
     fn test_get_next_token_brace() {
         // Arrange
@@ -1,5 +1,8 @@
+pub mod diagnostic;
 pub mod lexer;
 pub mod types;
 mod source_scanner;
 mod token_scanner;
+pub mod serializer;
 pub mod syntax_analyser;
+pub mod value;
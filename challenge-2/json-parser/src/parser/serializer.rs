@@ -0,0 +1,199 @@
+use std::fmt::Write;
+
+use super::value::{JsonValue, Number};
+
+// Writes a value back out as JSON, either compact on one line or pretty printed with each array
+// item and object member on its own line.
+pub struct Serializer {
+    // The number of spaces each level of nesting is indented by when pretty printing, or None for
+    // compact output.
+    pub indent: Option<usize>,
+    // Write object members sorted by key instead of in the order they were parsed.
+    pub sort_keys: bool,
+}
+
+// What is left to write. Like the parser, the serializer keeps its own stack instead of recursing.
+enum Step<'a> {
+    Value(&'a JsonValue, usize),
+    Key(&'a str),
+    Text(&'static str),
+    // A line break and the indent for the given depth, only when pretty printing.
+    NewLine(usize),
+}
+
+impl Serializer {
+    pub fn compact() -> Serializer {
+        Serializer { indent: None, sort_keys: false }
+    }
+
+    pub fn pretty(indent: usize) -> Serializer {
+        Serializer { indent: Some(indent), sort_keys: false }
+    }
+
+    pub fn serialize(&self, value: &JsonValue) -> String {
+        let mut output = String::new();
+        let mut steps = vec![Step::Value(value, 0)];
+        while let Some(step) = steps.pop() {
+            match step {
+                Step::Value(value, depth) => self.write_value(value, depth, &mut output, &mut steps),
+                Step::Key(key) => {
+                    write_string(key, &mut output);
+                    output.push_str(if self.indent.is_some() { ": " } else { ":" });
+                }
+                Step::Text(text) => output.push_str(text),
+                Step::NewLine(depth) => {
+                    if let Some(indent) = self.indent {
+                        output.push('\n');
+                        output.extend(std::iter::repeat_n(' ', indent * depth));
+                    }
+                }
+            }
+        }
+        output
+    }
+
+    // Writes a literal, or the opening of an array or object with the steps for the rest of it
+    // pushed in reverse.
+    fn write_value<'a>(&self, value: &'a JsonValue, depth: usize, output: &mut String, steps: &mut Vec<Step<'a>>) {
+        let (open, close, children) = match value {
+            JsonValue::Null => return output.push_str("null"),
+            JsonValue::Bool(boolean) => return output.push_str(if *boolean { "true" } else { "false" }),
+            JsonValue::Number(number) => return write_number(number, output),
+            JsonValue::String(text) => return write_string(text, output),
+            JsonValue::Array(items) => ("[", "]", items.iter().map(|item| (None, item)).collect::<Vec<_>>()),
+            JsonValue::Object(members) => {
+                let mut members: Vec<(Option<&str>, &JsonValue)> = members.iter().map(|(key, value)| (Some(key.as_str()), value)).collect();
+                if self.sort_keys {
+                    members.sort_by_key(|(key, _)| *key);
+                }
+                ("{", "}", members)
+            }
+        };
+
+        output.push_str(open);
+        if children.is_empty() {
+            output.push_str(close);
+            return;
+        }
+        steps.push(Step::Text(close));
+        steps.push(Step::NewLine(depth));
+        for (index, (key, child)) in children.into_iter().enumerate().rev() {
+            steps.push(Step::Value(child, depth + 1));
+            if let Some(key) = key {
+                steps.push(Step::Key(key));
+            }
+            steps.push(Step::NewLine(depth + 1));
+            if index > 0 {
+                steps.push(Step::Text(","));
+            }
+        }
+    }
+}
+
+// Integers, and numbers too large or small for a float, are written as they are. Floats are
+// written in the shortest form that reads back as the same float, always with a fraction or
+// exponent so they stay floats. JSON has no NaN or infinity, which the parser never makes, so a
+// value built with one is written as null.
+fn write_number(number: &Number, output: &mut String) {
+    match number {
+        Number::Integer(integer) => write!(output, "{}", integer).unwrap(),
+        Number::Float(float) if !float.is_finite() => output.push_str("null"),
+        Number::Float(float) => write!(output, "{:?}", float).unwrap(),
+        Number::Literal(lexeme) => output.push_str(lexeme),
+    }
+}
+
+// Quotes and backslashes are escaped, and so are control characters, with the short escapes where
+// JSON has them. Everything else, including non-ASCII characters, is written as it is.
+fn write_string(text: &str, output: &mut String) {
+    output.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            '\n' => output.push_str("\\n"),
+            '\r' => output.push_str("\\r"),
+            '\t' => output.push_str("\\t"),
+            '\u{8}' => output.push_str("\\b"),
+            '\u{c}' => output.push_str("\\f"),
+            c if (c as u32) < 0x20 => write!(output, "\\u{:04x}", c as u32).unwrap(),
+            c => output.push(c),
+        }
+    }
+    output.push('"');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::{lexer::Lexer, syntax_analyser::SyntaxAnalyzer, value::DeepDrop};
+
+    fn parse(source: &str) -> DeepDrop {
+        let tokens = Lexer::new().get_tokens(source).unwrap();
+        SyntaxAnalyzer::new().parse(tokens).unwrap()
+    }
+
+    #[test]
+    fn test_serialize_compact() {
+        // Arrange
+        let value = parse(r#" { "b" : [1, 2.5, {}], "a": null , "c": [ ], "d": {"e": true} } "#);
+
+        // Act
+        let output = Serializer::compact().serialize(&value);
+
+        // Assert
+        assert_eq!(output, r#"{"b":[1,2.5,{}],"a":null,"c":[],"d":{"e":true}}"#);
+    }
+
+    #[test]
+    fn test_serialize_pretty_with_sorted_keys() {
+        // Arrange
+        let value = parse(r#"{"b": [1, {"z": false, "y": "x"}], "a": {}}"#);
+        let serializer = Serializer { sort_keys: true, ..Serializer::pretty(2) };
+
+        // Act
+        let output = serializer.serialize(&value);
+
+        // Assert
+        assert_eq!(output, "{\n  \"a\": {},\n  \"b\": [\n    1,\n    {\n      \"y\": \"x\",\n      \"z\": false\n    }\n  ]\n}");
+    }
+
+    #[test]
+    fn test_serialize_should_escape_strings() {
+        // Arrange
+        let value = JsonValue::Array(vec![JsonValue::String("a\"b\\c\n\u{1}\u{8}é😀/".to_string())]);
+
+        // Act
+        let output = Serializer::compact().serialize(&value);
+
+        // Assert
+        assert_eq!(output, "[\"a\\\"b\\\\c\\n\\u0001\\bé😀/\"]");
+    }
+
+    #[test]
+    fn test_serialize_should_round_trip_numbers() {
+        // Arrange
+        let source = "[0,-0.0,9007199254740993,0.1,1e300,-1.5e-7,1000.0,123456789012345678901234567890,1e400,-1E-400]";
+        let value = parse(source);
+
+        // Act
+        let output = Serializer::compact().serialize(&value);
+
+        // Assert
+        assert_eq!(output, "[0,-0.0,9007199254740993,0.1,1e300,-1.5e-7,1000.0,1.2345678901234568e29,1e400,-1E-400]");
+        assert_eq!(parse(&output), value);
+    }
+
+    #[test]
+    fn test_serialize_deeply_nested_value() {
+        // Arrange
+        let depth = 100_000;
+        let value = parse(&format!("{}{}", "[".repeat(depth), "]".repeat(depth)));
+
+        // Act
+        let output = Serializer::compact().serialize(&value);
+
+        // Assert
+        assert_eq!(output.len(), depth * 2);
+    }
+}
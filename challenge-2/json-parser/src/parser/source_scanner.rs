@@ -7,6 +7,9 @@ pub struct SourceScanner<'a> {
     pub cursor: usize,
     line: usize,
     line_start: usize,
+    // The number of characters read since the start of the line, and where the token starts.
+    line_chars: usize,
+    token_column: usize,
     source: &'a str,
     length: usize,
     char_iter: Chars<'a>,
@@ -20,6 +23,8 @@ impl<'a> SourceScanner<'a> {
             cursor: 0,
             line: 1,
             line_start: 0,
+            line_chars: 0,
+            token_column: 1,
             source: source,
             length: source.len(),
             char_iter: source.chars().clone(),
@@ -36,12 +41,15 @@ impl<'a> SourceScanner<'a> {
     pub fn is_at_end(&self) -> bool {
         return self.cursor >= self.length;
     }
+    // The cursor is a byte offset, so lexemes can be sliced from the source.
     pub fn advance(&mut self) -> char {
-        self.cursor += 1;
         let char = self.char_iter.next().unwrap();
+        self.cursor += char.len_utf8();
+        self.line_chars += 1;
         if char == '\n' {
             self.line += 1;
             self.line_start = self.cursor;
+            self.line_chars = 0;
         }
         // dbg!(char);
         return char;
@@ -55,6 +63,7 @@ impl<'a> SourceScanner<'a> {
             self.advance();
         }
         self.token_start = self.cursor;
+        self.token_column = self.line_chars + 1;
     }
     pub fn add_token(&mut self, token_type: TokenType) {
         let lexeme = self.source[self.token_start..self.cursor].to_string();
@@ -62,6 +71,7 @@ impl<'a> SourceScanner<'a> {
             token_type: token_type,
             lexeme: lexeme,
             line: self.line,
+            column: self.token_column,
             position_start: self.token_start,
             position_end: self.cursor,
         });
@@ -71,6 +81,7 @@ impl<'a> SourceScanner<'a> {
         let scan_error = ScanError {
             line: self.line,
             line_start: self.line_start,
+            column: self.token_column,
             lexeme: lexeme,
             position_start: self.token_start,
             position_end: self.cursor,
@@ -190,4 +201,16 @@ mod tests {
         assert_eq!(scanner.cursor, 5);
         assert_eq!(scanner.line, 2);
     }
+
+    #[test]
+    fn test_advance_non_ascii() {
+        let mut scanner = SourceScanner::new("\u{e9}\u{1F600}a");
+        assert_eq!(scanner.advance(), '\u{e9}');
+        assert_eq!(scanner.cursor, 2);
+        assert_eq!(scanner.advance(), '\u{1F600}');
+        assert_eq!(scanner.cursor, 6);
+        assert_eq!(scanner.peek(), 'a');
+        assert_eq!(scanner.advance(), 'a');
+        assert!(scanner.is_at_end());
+    }
 }
@@ -1,6 +1,13 @@
 use core::fmt;
+use std::collections::HashMap;
+use std::ops::Range;
 
-use super::{types::{Token, TokenType}, token_scanner::TokenScanner};
+use super::{types::{Token, TokenType}, token_scanner::TokenScanner, value::{self, DeepDrop, JsonValue}};
+
+// The tokens a document, or a value, can start with.
+const DOCUMENT_START: &[TokenType] = &[TokenType::LeftBrace, TokenType::LeftBracket];
+const VALUE_START: &[TokenType] = &[TokenType::String, TokenType::Number, TokenType::Boolean, TokenType::Null, TokenType::LeftBrace, TokenType::LeftBracket];
+const ARRAY_ITEM_START: &[TokenType] = &[TokenType::String, TokenType::Number, TokenType::Boolean, TokenType::Null, TokenType::LeftBrace, TokenType::LeftBracket, TokenType::RightBracket];
 
 #[derive(Copy, Clone, PartialEq, Eq)]
 enum StateType {
@@ -10,7 +17,8 @@ enum StateType {
     Array,
     ArrayItem,
     ArrayItemEnd,
-    Invalid,
+    // The current token, or the end of the input, isn't one of the tokens that could come next.
+    Invalid(&'static [TokenType]),
     End
 }
 impl fmt::Display for StateType {
@@ -22,7 +30,7 @@ impl fmt::Display for StateType {
             StateType::Array => write!(f, "Array"),
             StateType::ArrayItem => write!(f, "ArrayItem"),
             StateType::ArrayItemEnd => write!(f, "ArrayItemEnd"),
-            StateType::Invalid => write!(f, "Invalid"),
+            StateType::Invalid(_) => write!(f, "Invalid"),
             StateType::End => write!(f, "End"),
         }
     }
@@ -34,6 +42,7 @@ pub enum ErrorType {
     EmptyTokens,
     InvalidInitialState,
     UnexpectedToken,
+    UnexpectedEnd,
     UnexpectedState,
     TrailingTokens,
 }
@@ -43,12 +52,68 @@ impl fmt::Display for ErrorType {
             ErrorType::EmptyTokens => write!(f, "EmptyTokens"),
             ErrorType::InvalidInitialState => write!(f, "InvalidInitialState"),
             ErrorType::UnexpectedToken => write!(f, "UnexpectedToken"),
+            ErrorType::UnexpectedEnd => write!(f, "UnexpectedEnd"),
             ErrorType::UnexpectedState => write!(f, "UnexpectedState"),
             ErrorType::TrailingTokens => write!(f, "TrailingTokens"),
         }
     }
 }
 
+#[derive(Debug, PartialEq, Eq)]
+pub struct SyntaxError {
+    pub error_type: ErrorType,
+    // The offending token, or None when the input ended too soon.
+    pub token: Option<Token>,
+    pub line: usize,
+    // The character the problem starts at in its line, counting from 1.
+    pub column: usize,
+    // Byte offsets of the problem in the source. At the end of the input the span is empty.
+    pub span: Range<usize>,
+    // The tokens that could have come instead. Empty when the document should have ended.
+    pub expected: &'static [TokenType],
+}
+impl SyntaxError {
+    fn new(error_type: ErrorType, scanner: &TokenScanner, expected: &'static [TokenType]) -> SyntaxError {
+        if let Some(token) = scanner.current() {
+            return SyntaxError {
+                error_type,
+                line: token.line,
+                column: token.column,
+                span: token.position_start..token.position_end,
+                token: Some(token),
+                expected,
+            };
+        }
+
+        // Just after the last token.
+        let (line, column, end) = scanner.tokens.last().map_or((1, 1, 0), |last| (last.line, last.column + last.lexeme.chars().count(), last.position_end));
+        SyntaxError { error_type, token: None, line, column, span: end..end, expected }
+    }
+
+    pub fn message(&self) -> String {
+        if self.error_type == ErrorType::UnexpectedState {
+            return "the parser reached an unexpected state".to_string();
+        }
+
+        let expected: Vec<&str> = self.expected.iter().map(|token_type| token_type.description()).collect();
+        let expected = match expected.split_last() {
+            None => "end of input".to_string(),
+            Some((last, [])) => last.to_string(),
+            Some((last, rest)) => format!("{} or {}", rest.join(", "), last),
+        };
+        let found = match &self.token {
+            Some(token) => format!("`{}`", token.lexeme),
+            None => "end of input".to_string(),
+        };
+        format!("expected {}, found {}", expected, found)
+    }
+}
+impl fmt::Display for SyntaxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[line {}, column {}] {}: {}", self.line, self.column, self.error_type, self.message())
+    }
+}
+
 pub struct SyntaxAnalyzer {
     stack: Vec<StateType>,
 }
@@ -60,28 +125,25 @@ impl SyntaxAnalyzer {
         }
     }
 
-    pub fn parse(& mut self, tokens: Vec<Token>) -> Result<(), ErrorType> {
+    pub fn parse(& mut self, tokens: Vec<Token>) -> Result<DeepDrop, SyntaxError> {
+        let mut scanner = TokenScanner::new(&tokens);
         if tokens.len() == 0 {
-            return Err(ErrorType::EmptyTokens);
+            return Err(SyntaxError::new(ErrorType::EmptyTokens, &scanner, DOCUMENT_START));
         }
-
-        let mut scanner = TokenScanner::new(&tokens);
+        self.stack.clear();
 
         let first_token: Token = scanner.current().unwrap();
         let mut state = match first_token.token_type {
             TokenType::LeftBrace => StateType::Object,
             TokenType::LeftBracket => StateType::Array,
-            _ => StateType::Invalid
+            _ => return Err(SyntaxError::new(ErrorType::InvalidInitialState, &scanner, DOCUMENT_START)),
         };
 
-        if state == StateType::Invalid {
-            return Err(ErrorType::InvalidInitialState);
-        }
-
         while state != StateType::End {
 
-            if state == StateType::Invalid {
-                return Err(ErrorType::UnexpectedToken);
+            if let StateType::Invalid(expected) = state {
+                let error_type = if scanner.current().is_some() { ErrorType::UnexpectedToken } else { ErrorType::UnexpectedEnd };
+                return Err(SyntaxError::new(error_type, &scanner, expected));
             }
 
             let next_state_result: Option<StateType> = match state {
@@ -91,7 +153,7 @@ impl SyntaxAnalyzer {
                 StateType::Array => SyntaxAnalyzer::parse_array(& mut scanner),
                 StateType::ArrayItem => SyntaxAnalyzer::parse_value(&mut scanner),
                 StateType::ArrayItemEnd => SyntaxAnalyzer::parse_array_item_end(& mut scanner),
-                _ => return Err(ErrorType::UnexpectedState)
+                _ => return Err(SyntaxError::new(ErrorType::UnexpectedState, &scanner, &[]))
             };
 
             if next_state_result.is_some() {
@@ -117,46 +179,98 @@ impl SyntaxAnalyzer {
             }
         }
 
-        let current = scanner.current().unwrap();
-        if !scanner.is_at_end() || (current.token_type != TokenType::RightBrace && current.token_type != TokenType::RightBracket) {
-            print!("{} ", current);
-            return Err(ErrorType::TrailingTokens);
+        // The document ends with the token that closes it, so anything after that is reported.
+        if !scanner.is_at_end() {
+            scanner.advance();
+            return Err(SyntaxError::new(ErrorType::TrailingTokens, &scanner, &[]));
         }
 
-        Ok(())
+        let mut scanner = TokenScanner::new(&tokens);
+        Ok(DeepDrop::new(SyntaxAnalyzer::build_value(&mut scanner)))
+    }
+
+    // Builds the value tree once the tokens are known to be valid. Like the analyser this keeps
+    // its own stack, of the arrays and objects still open, so deep nesting can't overflow the call
+    // stack. Each one is kept with the key it will be stored under in the object around it, and
+    // objects with the position of each of their keys so far.
+    fn build_value(scanner: & mut TokenScanner) -> JsonValue {
+        let mut open: Vec<(Option<String>, JsonValue, HashMap<String, usize>)> = vec![];
+        let mut key: Option<String> = None;
+        loop {
+            let current: Token = scanner.current().unwrap();
+            let value = match current.token_type {
+                TokenType::LeftBrace => {
+                    open.push((key.take(), JsonValue::Object(vec![]), HashMap::new()));
+                    None
+                }
+                TokenType::LeftBracket => {
+                    open.push((key.take(), JsonValue::Array(vec![]), HashMap::new()));
+                    None
+                }
+                TokenType::RightBrace | TokenType::RightBracket => {
+                    let (parent_key, container, _) = open.pop().unwrap();
+                    key = parent_key;
+                    Some(container)
+                }
+                TokenType::Colon | TokenType::Comma => None,
+                TokenType::String if key.is_none() && matches!(open.last(), Some((_, JsonValue::Object(_), _))) => {
+                    key = Some(value::unescape(&current.lexeme));
+                    None
+                }
+                _ => JsonValue::from_literal(&current),
+            };
+
+            if let Some(value) = value {
+                match open.last_mut() {
+                    None => return value,
+                    Some((_, JsonValue::Object(members), positions)) => value::insert_member(members, positions, key.take().unwrap(), value),
+                    Some((_, JsonValue::Array(items), _)) => items.push(value),
+                    Some(_) => unreachable!("only arrays and objects are open"),
+                }
+            }
+            scanner.advance();
+        }
     }
 
     fn parse_object(scanner: & mut TokenScanner) -> Option<StateType> {
-        let current: Token = scanner.current().unwrap();
+        let Some(current) = scanner.current() else {
+            return Some(StateType::Invalid(&[TokenType::LeftBrace]));
+        };
         if current.token_type != TokenType::LeftBrace {
-            return Some(StateType::Invalid);
+            return Some(StateType::Invalid(&[TokenType::LeftBrace]));
         }
         
-        let next = scanner.advance().unwrap();
+        let expected = &[TokenType::String, TokenType::RightBrace];
+        let Some(next) = scanner.advance() else {
+            return Some(StateType::Invalid(expected));
+        };
         match next.token_type {
             TokenType::RightBrace => return None,
             TokenType::String => return Some(StateType::ObjectProperty),
-            _ => return Some(StateType::Invalid)
+            _ => return Some(StateType::Invalid(expected))
         }
     }
 
     fn parse_object_property(scanner: & mut TokenScanner) -> Option<StateType> {
-        let mut current: Token = scanner.current().unwrap();
-        if current.token_type != TokenType::String {
-            return Some(StateType::Invalid);
+        match scanner.current() {
+            Some(current) if current.token_type == TokenType::String => {}
+            _ => return Some(StateType::Invalid(&[TokenType::String])),
         }
 
-        current = scanner.advance().unwrap();
-        if current.token_type != TokenType::Colon {
-            return Some(StateType::Invalid);
+        match scanner.advance() {
+            Some(current) if current.token_type == TokenType::Colon => {}
+            _ => return Some(StateType::Invalid(&[TokenType::Colon])),
         }
 
-        scanner.advance().unwrap();
+        scanner.advance();
         SyntaxAnalyzer::parse_value(scanner)
     }
 
     fn parse_object_property_end(scanner: & mut TokenScanner) -> Option<StateType> {
-        let current: Token = scanner.current().unwrap();
+        let expected = &[TokenType::Comma, TokenType::RightBrace];
+        let Some(current) = scanner.current() else {
+            return Some(StateType::Invalid(expected));
+        };
 
         if current.token_type == TokenType::Comma {
             scanner.advance();
@@ -166,25 +280,32 @@ impl SyntaxAnalyzer {
             return None;
         }
         
-        return Some(StateType::Invalid);
+        return Some(StateType::Invalid(expected));
     }
 
     fn parse_array(scanner: & mut TokenScanner) -> Option<StateType> {
-        let current: Token = scanner.current().unwrap();
+        let Some(current) = scanner.current() else {
+            return Some(StateType::Invalid(&[TokenType::LeftBracket]));
+        };
         if current.token_type != TokenType::LeftBracket {
-            return Some(StateType::Invalid);
+            return Some(StateType::Invalid(&[TokenType::LeftBracket]));
         }
 
-        let next = scanner.advance().unwrap();
+        let Some(next) = scanner.advance() else {
+            return Some(StateType::Invalid(ARRAY_ITEM_START));
+        };
         match next.token_type {
             TokenType::RightBracket => return None,
-            TokenType::Comma => return Some(StateType::Invalid),
+            TokenType::Comma => return Some(StateType::Invalid(ARRAY_ITEM_START)),
             _ => return Some(StateType::ArrayItem),
         }        
     }
 
     fn parse_array_item_end(scanner: & mut TokenScanner) -> Option<StateType> {
-        let current: Token = scanner.current().unwrap();
+        let expected = &[TokenType::Comma, TokenType::RightBracket];
+        let Some(current) = scanner.current() else {
+            return Some(StateType::Invalid(expected));
+        };
 
         if current.token_type == TokenType::Comma {
             scanner.advance();
@@ -194,11 +315,13 @@ impl SyntaxAnalyzer {
             return None;
         }
         
-        return Some(StateType::Invalid);
+        return Some(StateType::Invalid(expected));
     }
 
     fn parse_value(scanner: & mut TokenScanner) -> Option<StateType> {
-        let current: Token = scanner.current().unwrap();
+        let Some(current) = scanner.current() else {
+            return Some(StateType::Invalid(VALUE_START));
+        };
         if current.token_type == TokenType::LeftBrace {
             return SyntaxAnalyzer::parse_object(scanner);
         }
@@ -211,13 +334,14 @@ impl SyntaxAnalyzer {
             return None;
         }
 
-        Some(StateType::Invalid)
+        Some(StateType::Invalid(VALUE_START))
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::parser::{lexer::Lexer, value::{DeepDrop, Number}};
 
     #[test]
     fn test_parse_should_return_err_if_tokens_empty() {
@@ -229,14 +353,14 @@ mod tests {
         let result = syntax_analyser.parse(input);
 
         //Assert
-        assert_eq!(result, Err(ErrorType::EmptyTokens));
+        assert_eq!(result.map_err(|error| error.error_type), Err(ErrorType::EmptyTokens));
     }
 
     #[test]
     fn test_parse_should_return_err_if_first_token_is_not_valid() {
         //Arrange
         let input: Vec<Token> = vec![
-            Token { token_type: TokenType::Boolean, lexeme: "true".to_string(), line: 1, position_start: 0, position_end: 0 }
+            Token { token_type: TokenType::Boolean, lexeme: "true".to_string(), line: 1, column: 0, position_start: 0, position_end: 0 }
         ];
         let mut syntax_analyser = SyntaxAnalyzer::new();
 
@@ -244,15 +368,15 @@ mod tests {
         let result = syntax_analyser.parse(input);
 
         //Assert
-        assert_eq!(result, Err(ErrorType::InvalidInitialState));
+        assert_eq!(result.map_err(|error| error.error_type), Err(ErrorType::InvalidInitialState));
     }
 
     #[test]
     fn test_parse_should_return_true_for_empty_object() {
         //Arrange
         let input: Vec<Token> = vec![
-            Token { token_type: TokenType::LeftBrace, lexeme: "{".to_string(), line: 0, position_start: 0, position_end: 1 },
-            Token { token_type: TokenType::RightBrace, lexeme: "}".to_string(), line: 0, position_start: 1, position_end: 2 },
+            Token { token_type: TokenType::LeftBrace, lexeme: "{".to_string(), line: 0, column: 0, position_start: 0, position_end: 1 },
+            Token { token_type: TokenType::RightBrace, lexeme: "}".to_string(), line: 0, column: 0, position_start: 1, position_end: 2 },
         ];
         let mut syntax_analyser = SyntaxAnalyzer::new();
 
@@ -267,11 +391,11 @@ mod tests {
     fn test_parse_should_return_true_for_object_with_single_value() {
         //Arrange
         let input: Vec<Token> = vec![
-            Token { token_type: TokenType::LeftBrace, lexeme: "{".to_string(), line: 0, position_start: 0, position_end: 1 },
-            Token { token_type: TokenType::String, lexeme: "test".to_string(), line: 0, position_start: 1, position_end: 7 },
-            Token { token_type: TokenType::Colon, lexeme: ":".to_string(), line: 0, position_start: 7, position_end: 8 },
-            Token { token_type: TokenType::String, lexeme: "value".to_string(), line: 0, position_start: 8, position_end: 15 },
-            Token { token_type: TokenType::RightBrace, lexeme: "}".to_string(), line: 0, position_start: 15, position_end: 16 },
+            Token { token_type: TokenType::LeftBrace, lexeme: "{".to_string(), line: 0, column: 0, position_start: 0, position_end: 1 },
+            Token { token_type: TokenType::String, lexeme: "test".to_string(), line: 0, column: 0, position_start: 1, position_end: 7 },
+            Token { token_type: TokenType::Colon, lexeme: ":".to_string(), line: 0, column: 0, position_start: 7, position_end: 8 },
+            Token { token_type: TokenType::String, lexeme: "value".to_string(), line: 0, column: 0, position_start: 8, position_end: 15 },
+            Token { token_type: TokenType::RightBrace, lexeme: "}".to_string(), line: 0, column: 0, position_start: 15, position_end: 16 },
         ];
         let mut syntax_analyser = SyntaxAnalyzer::new();
 
@@ -286,15 +410,15 @@ mod tests {
     fn test_parse_should_return_true_for_object_with_multiple_values() {
         //Arrange
         let input: Vec<Token> = vec![
-            Token { token_type: TokenType::LeftBrace, lexeme: "{".to_string(), line: 0, position_start: 0, position_end: 1 },
-            Token { token_type: TokenType::String, lexeme: "\"test\"".to_string(), line: 0, position_start: 1, position_end: 7 },
-            Token { token_type: TokenType::Colon, lexeme: ":".to_string(), line: 0, position_start: 7, position_end: 8 },
-            Token { token_type: TokenType::String, lexeme: "\"value\"".to_string(), line: 0, position_start: 8, position_end: 15 },
-            Token { token_type: TokenType::Comma, lexeme: ",".to_string(), line: 0, position_start: 15, position_end: 16 },
-            Token { token_type: TokenType::String, lexeme: "\"test2\"".to_string(), line: 0, position_start: 16, position_end: 23 },
-            Token { token_type: TokenType::Colon, lexeme: ":".to_string(), line: 0, position_start: 23, position_end: 24 },
-            Token { token_type: TokenType::String, lexeme: "1".to_string(), line: 0, position_start: 25, position_end: 26 },
-            Token { token_type: TokenType::RightBrace, lexeme: "}".to_string(), line: 0, position_start: 26, position_end: 27 },
+            Token { token_type: TokenType::LeftBrace, lexeme: "{".to_string(), line: 0, column: 0, position_start: 0, position_end: 1 },
+            Token { token_type: TokenType::String, lexeme: "\"test\"".to_string(), line: 0, column: 0, position_start: 1, position_end: 7 },
+            Token { token_type: TokenType::Colon, lexeme: ":".to_string(), line: 0, column: 0, position_start: 7, position_end: 8 },
+            Token { token_type: TokenType::String, lexeme: "\"value\"".to_string(), line: 0, column: 0, position_start: 8, position_end: 15 },
+            Token { token_type: TokenType::Comma, lexeme: ",".to_string(), line: 0, column: 0, position_start: 15, position_end: 16 },
+            Token { token_type: TokenType::String, lexeme: "\"test2\"".to_string(), line: 0, column: 0, position_start: 16, position_end: 23 },
+            Token { token_type: TokenType::Colon, lexeme: ":".to_string(), line: 0, column: 0, position_start: 23, position_end: 24 },
+            Token { token_type: TokenType::String, lexeme: "1".to_string(), line: 0, column: 0, position_start: 25, position_end: 26 },
+            Token { token_type: TokenType::RightBrace, lexeme: "}".to_string(), line: 0, column: 0, position_start: 26, position_end: 27 },
         ];
         let mut syntax_analyser = SyntaxAnalyzer::new();
 
@@ -309,19 +433,19 @@ mod tests {
     fn test_parse_should_return_true_for_nested_object() {
         //Arrange
         let input: Vec<Token> = vec![
-            Token { token_type: TokenType::LeftBrace, lexeme: "{".to_string(), line: 0, position_start: 0, position_end: 0 },
-            Token { token_type: TokenType::String, lexeme: "the key".to_string(), line: 0, position_start: 0, position_end: 0 },
-            Token { token_type: TokenType::Colon, lexeme: ":".to_string(), line: 0, position_start: 0, position_end: 0 },
-            Token { token_type: TokenType::LeftBrace, lexeme: "{".to_string(), line: 0, position_start: 0, position_end: 0 },
-            Token { token_type: TokenType::String, lexeme: "test".to_string(), line: 0, position_start: 0, position_end: 0 },
-            Token { token_type: TokenType::Colon, lexeme: ":".to_string(), line: 0, position_start: 0, position_end: 0 },
-            Token { token_type: TokenType::String, lexeme: "value".to_string(), line: 0, position_start: 0, position_end: 0 },
-            Token { token_type: TokenType::Comma, lexeme: ",".to_string(), line: 0, position_start: 0, position_end: 0 },
-            Token { token_type: TokenType::String, lexeme: "test2".to_string(), line: 0, position_start: 0, position_end: 0 },
-            Token { token_type: TokenType::Colon, lexeme: ":".to_string(), line: 0, position_start: 0, position_end: 0 },
-            Token { token_type: TokenType::String, lexeme: "value2".to_string(), line: 0, position_start: 0, position_end: 0 },
-            Token { token_type: TokenType::RightBrace, lexeme: "}".to_string(), line: 0, position_start: 0, position_end: 0 },
-            Token { token_type: TokenType::RightBrace, lexeme: "}".to_string(), line: 0, position_start: 0, position_end: 0 },
+            Token { token_type: TokenType::LeftBrace, lexeme: "{".to_string(), line: 0, column: 0, position_start: 0, position_end: 0 },
+            Token { token_type: TokenType::String, lexeme: "the key".to_string(), line: 0, column: 0, position_start: 0, position_end: 0 },
+            Token { token_type: TokenType::Colon, lexeme: ":".to_string(), line: 0, column: 0, position_start: 0, position_end: 0 },
+            Token { token_type: TokenType::LeftBrace, lexeme: "{".to_string(), line: 0, column: 0, position_start: 0, position_end: 0 },
+            Token { token_type: TokenType::String, lexeme: "test".to_string(), line: 0, column: 0, position_start: 0, position_end: 0 },
+            Token { token_type: TokenType::Colon, lexeme: ":".to_string(), line: 0, column: 0, position_start: 0, position_end: 0 },
+            Token { token_type: TokenType::String, lexeme: "value".to_string(), line: 0, column: 0, position_start: 0, position_end: 0 },
+            Token { token_type: TokenType::Comma, lexeme: ",".to_string(), line: 0, column: 0, position_start: 0, position_end: 0 },
+            Token { token_type: TokenType::String, lexeme: "test2".to_string(), line: 0, column: 0, position_start: 0, position_end: 0 },
+            Token { token_type: TokenType::Colon, lexeme: ":".to_string(), line: 0, column: 0, position_start: 0, position_end: 0 },
+            Token { token_type: TokenType::String, lexeme: "value2".to_string(), line: 0, column: 0, position_start: 0, position_end: 0 },
+            Token { token_type: TokenType::RightBrace, lexeme: "}".to_string(), line: 0, column: 0, position_start: 0, position_end: 0 },
+            Token { token_type: TokenType::RightBrace, lexeme: "}".to_string(), line: 0, column: 0, position_start: 0, position_end: 0 },
         ];
         let mut syntax_analyser = SyntaxAnalyzer::new();
 
@@ -336,10 +460,10 @@ mod tests {
     fn test_parse_should_return_error_for_unclosed_object() {
         //Arrange
         let input: Vec<Token> = vec![
-            Token { token_type: TokenType::LeftBrace, lexeme: "{".to_string(), line: 0, position_start: 0, position_end: 1 },
-            Token { token_type: TokenType::String, lexeme: "\"test\"".to_string(), line: 0, position_start: 1, position_end: 7 },
-            Token { token_type: TokenType::Colon, lexeme: ":".to_string(), line: 0, position_start: 7, position_end: 8 },
-            Token { token_type: TokenType::String, lexeme: "\"value\"".to_string(), line: 0, position_start: 8, position_end: 15 },
+            Token { token_type: TokenType::LeftBrace, lexeme: "{".to_string(), line: 0, column: 0, position_start: 0, position_end: 1 },
+            Token { token_type: TokenType::String, lexeme: "\"test\"".to_string(), line: 0, column: 0, position_start: 1, position_end: 7 },
+            Token { token_type: TokenType::Colon, lexeme: ":".to_string(), line: 0, column: 0, position_start: 7, position_end: 8 },
+            Token { token_type: TokenType::String, lexeme: "\"value\"".to_string(), line: 0, column: 0, position_start: 8, position_end: 15 },
         ];
         let mut syntax_analyser = SyntaxAnalyzer::new();
 
@@ -347,15 +471,15 @@ mod tests {
         let result = syntax_analyser.parse(input.into());
 
         //Assert
-        assert_eq!(result, Err(ErrorType::UnexpectedToken));
+        assert_eq!(result.map_err(|error| error.error_type), Err(ErrorType::UnexpectedEnd));
     }
 
     #[test]
     fn test_parse_should_return_true_for_empty_array() {
         //Arrange
         let input: Vec<Token> = vec![
-            Token { token_type: TokenType::LeftBracket, lexeme: "[".to_string(), line: 0, position_start: 0, position_end: 1 },
-            Token { token_type: TokenType::RightBracket, lexeme: "]".to_string(), line: 0, position_start: 1, position_end: 2 },
+            Token { token_type: TokenType::LeftBracket, lexeme: "[".to_string(), line: 0, column: 0, position_start: 0, position_end: 1 },
+            Token { token_type: TokenType::RightBracket, lexeme: "]".to_string(), line: 0, column: 0, position_start: 1, position_end: 2 },
         ];
         let mut syntax_analyser = SyntaxAnalyzer::new();
 
@@ -370,9 +494,9 @@ mod tests {
     fn test_parse_should_return_true_for_array_with_single_value() {
         //Arrange
         let input: Vec<Token> = vec![
-            Token { token_type: TokenType::LeftBracket, lexeme: "[".to_string(), line: 0, position_start: 0, position_end: 1 },
-            Token { token_type: TokenType::String, lexeme: "test".to_string(), line: 0, position_start: 1, position_end: 7 },
-            Token { token_type: TokenType::RightBracket, lexeme: "]".to_string(), line: 0, position_start: 7, position_end: 8 },
+            Token { token_type: TokenType::LeftBracket, lexeme: "[".to_string(), line: 0, column: 0, position_start: 0, position_end: 1 },
+            Token { token_type: TokenType::String, lexeme: "test".to_string(), line: 0, column: 0, position_start: 1, position_end: 7 },
+            Token { token_type: TokenType::RightBracket, lexeme: "]".to_string(), line: 0, column: 0, position_start: 7, position_end: 8 },
         ];
         let mut syntax_analyser = SyntaxAnalyzer::new();
 
@@ -387,11 +511,11 @@ mod tests {
     fn test_parse_should_return_true_for_nested_array() {
         //Arrange
         let input: Vec<Token> = vec![
-            Token { token_type: TokenType::LeftBracket, lexeme: "[".to_string(), line: 0, position_start: 0, position_end: 0 },
-            Token { token_type: TokenType::LeftBracket, lexeme: "[".to_string(), line: 0, position_start: 0, position_end: 0 },
-            Token { token_type: TokenType::String, lexeme: "test".to_string(), line: 0, position_start: 0, position_end: 0 },
-            Token { token_type: TokenType::RightBracket, lexeme: "]".to_string(), line: 0, position_start: 0, position_end: 0 },
-            Token { token_type: TokenType::RightBracket, lexeme: "]".to_string(), line: 0, position_start: 0, position_end: 0 },
+            Token { token_type: TokenType::LeftBracket, lexeme: "[".to_string(), line: 0, column: 0, position_start: 0, position_end: 0 },
+            Token { token_type: TokenType::LeftBracket, lexeme: "[".to_string(), line: 0, column: 0, position_start: 0, position_end: 0 },
+            Token { token_type: TokenType::String, lexeme: "test".to_string(), line: 0, column: 0, position_start: 0, position_end: 0 },
+            Token { token_type: TokenType::RightBracket, lexeme: "]".to_string(), line: 0, column: 0, position_start: 0, position_end: 0 },
+            Token { token_type: TokenType::RightBracket, lexeme: "]".to_string(), line: 0, column: 0, position_start: 0, position_end: 0 },
         ];
         let mut syntax_analyser = SyntaxAnalyzer::new();
 
@@ -406,10 +530,10 @@ mod tests {
     fn test_parse_should_return_true_for_array_with_object() {
         //Arrange
         let input: Vec<Token> = vec![
-            Token { token_type: TokenType::LeftBracket, lexeme: "[".to_string(), line: 0, position_start: 0, position_end: 0 },
-            Token { token_type: TokenType::LeftBrace, lexeme: "{".to_string(), line: 0, position_start: 0, position_end: 0 },
-            Token { token_type: TokenType::RightBrace, lexeme: "}".to_string(), line: 0, position_start: 0, position_end: 0 },
-            Token { token_type: TokenType::RightBracket, lexeme: "]".to_string(), line: 0, position_start: 0, position_end: 0 },
+            Token { token_type: TokenType::LeftBracket, lexeme: "[".to_string(), line: 0, column: 0, position_start: 0, position_end: 0 },
+            Token { token_type: TokenType::LeftBrace, lexeme: "{".to_string(), line: 0, column: 0, position_start: 0, position_end: 0 },
+            Token { token_type: TokenType::RightBrace, lexeme: "}".to_string(), line: 0, column: 0, position_start: 0, position_end: 0 },
+            Token { token_type: TokenType::RightBracket, lexeme: "]".to_string(), line: 0, column: 0, position_start: 0, position_end: 0 },
         ];
         let mut syntax_analyser = SyntaxAnalyzer::new();
 
@@ -424,18 +548,18 @@ mod tests {
     fn test_parse_should_return_true_for_mixed_array() {
         //Arrange
         let input: Vec<Token> = vec![
-            Token { token_type: TokenType::LeftBracket, lexeme: "[".to_string(), line: 0, position_start: 0, position_end: 0 },
-            Token { token_type: TokenType::String, lexeme: "test".to_string(), line: 0, position_start: 0, position_end: 0 },
-            Token { token_type: TokenType::Comma, lexeme: ",".to_string(), line: 0, position_start: 0, position_end: 0 },
-            Token { token_type: TokenType::LeftBracket, lexeme: "[".to_string(), line: 0, position_start: 0, position_end: 0 },
-            Token { token_type: TokenType::RightBracket, lexeme: "]".to_string(), line: 0, position_start: 0, position_end: 0 },
-            Token { token_type: TokenType::Comma, lexeme: ",".to_string(), line: 0, position_start: 0, position_end: 0 },
-            Token { token_type: TokenType::LeftBrace, lexeme: "{".to_string(), line: 0, position_start: 0, position_end: 0 },
-            Token { token_type: TokenType::String, lexeme: "test".to_string(), line: 0, position_start: 0, position_end: 0 },
-            Token { token_type: TokenType::Colon, lexeme: ":".to_string(), line: 0, position_start: 0, position_end: 0 },
-            Token { token_type: TokenType::Number, lexeme: "123".to_string(), line: 0, position_start: 0, position_end: 0 },
-            Token { token_type: TokenType::RightBrace, lexeme: "}".to_string(), line: 0, position_start: 0, position_end: 0 },
-            Token { token_type: TokenType::RightBracket, lexeme: "]".to_string(), line: 0, position_start: 0, position_end: 0 },
+            Token { token_type: TokenType::LeftBracket, lexeme: "[".to_string(), line: 0, column: 0, position_start: 0, position_end: 0 },
+            Token { token_type: TokenType::String, lexeme: "test".to_string(), line: 0, column: 0, position_start: 0, position_end: 0 },
+            Token { token_type: TokenType::Comma, lexeme: ",".to_string(), line: 0, column: 0, position_start: 0, position_end: 0 },
+            Token { token_type: TokenType::LeftBracket, lexeme: "[".to_string(), line: 0, column: 0, position_start: 0, position_end: 0 },
+            Token { token_type: TokenType::RightBracket, lexeme: "]".to_string(), line: 0, column: 0, position_start: 0, position_end: 0 },
+            Token { token_type: TokenType::Comma, lexeme: ",".to_string(), line: 0, column: 0, position_start: 0, position_end: 0 },
+            Token { token_type: TokenType::LeftBrace, lexeme: "{".to_string(), line: 0, column: 0, position_start: 0, position_end: 0 },
+            Token { token_type: TokenType::String, lexeme: "test".to_string(), line: 0, column: 0, position_start: 0, position_end: 0 },
+            Token { token_type: TokenType::Colon, lexeme: ":".to_string(), line: 0, column: 0, position_start: 0, position_end: 0 },
+            Token { token_type: TokenType::Number, lexeme: "123".to_string(), line: 0, column: 0, position_start: 0, position_end: 0 },
+            Token { token_type: TokenType::RightBrace, lexeme: "}".to_string(), line: 0, column: 0, position_start: 0, position_end: 0 },
+            Token { token_type: TokenType::RightBracket, lexeme: "]".to_string(), line: 0, column: 0, position_start: 0, position_end: 0 },
         ];
         let mut syntax_analyser = SyntaxAnalyzer::new();
 
@@ -450,13 +574,13 @@ mod tests {
     fn test_parse_should_return_true_2d_array() {
         //Arrange
         let input: Vec<Token> = vec![
-            Token { token_type: TokenType::LeftBracket, lexeme: "[".to_string(), line: 0, position_start: 0, position_end: 0 },
-            Token { token_type: TokenType::LeftBracket, lexeme: "[".to_string(), line: 0, position_start: 0, position_end: 0 },
-            Token { token_type: TokenType::RightBracket, lexeme: "]".to_string(), line: 0, position_start: 0, position_end: 0 },
-            Token { token_type: TokenType::Comma, lexeme: ",".to_string(), line: 0, position_start: 0, position_end: 0 },
-            Token { token_type: TokenType::LeftBracket, lexeme: "[".to_string(), line: 0, position_start: 0, position_end: 0 },
-            Token { token_type: TokenType::RightBracket, lexeme: "]".to_string(), line: 0, position_start: 0, position_end: 0 },
-            Token { token_type: TokenType::RightBracket, lexeme: "]".to_string(), line: 0, position_start: 0, position_end: 0 },
+            Token { token_type: TokenType::LeftBracket, lexeme: "[".to_string(), line: 0, column: 0, position_start: 0, position_end: 0 },
+            Token { token_type: TokenType::LeftBracket, lexeme: "[".to_string(), line: 0, column: 0, position_start: 0, position_end: 0 },
+            Token { token_type: TokenType::RightBracket, lexeme: "]".to_string(), line: 0, column: 0, position_start: 0, position_end: 0 },
+            Token { token_type: TokenType::Comma, lexeme: ",".to_string(), line: 0, column: 0, position_start: 0, position_end: 0 },
+            Token { token_type: TokenType::LeftBracket, lexeme: "[".to_string(), line: 0, column: 0, position_start: 0, position_end: 0 },
+            Token { token_type: TokenType::RightBracket, lexeme: "]".to_string(), line: 0, column: 0, position_start: 0, position_end: 0 },
+            Token { token_type: TokenType::RightBracket, lexeme: "]".to_string(), line: 0, column: 0, position_start: 0, position_end: 0 },
         ];
         let mut syntax_analyser = SyntaxAnalyzer::new();
 
@@ -471,10 +595,10 @@ mod tests {
     fn test_parse_should_return_err_for_array_with_trailing_comma() {
         //Arrange
         let input: Vec<Token> = vec![
-            Token { token_type: TokenType::LeftBracket, lexeme: "[".to_string(), line: 0, position_start: 0, position_end: 0 },
-            Token { token_type: TokenType::String, lexeme: "test".to_string(), line: 0, position_start: 0, position_end: 0 },
-            Token { token_type: TokenType::RightBracket, lexeme: "]".to_string(), line: 0, position_start: 0, position_end: 0 },
-            Token { token_type: TokenType::Comma, lexeme: ",".to_string(), line: 0, position_start: 0, position_end: 0 },
+            Token { token_type: TokenType::LeftBracket, lexeme: "[".to_string(), line: 0, column: 0, position_start: 0, position_end: 0 },
+            Token { token_type: TokenType::String, lexeme: "test".to_string(), line: 0, column: 0, position_start: 0, position_end: 0 },
+            Token { token_type: TokenType::RightBracket, lexeme: "]".to_string(), line: 0, column: 0, position_start: 0, position_end: 0 },
+            Token { token_type: TokenType::Comma, lexeme: ",".to_string(), line: 0, column: 0, position_start: 0, position_end: 0 },
         ];
         let mut syntax_analyser = SyntaxAnalyzer::new();
 
@@ -485,4 +609,102 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_parse_should_return_value_tree() {
+        //Arrange
+        let source = r#"{"name": "caf\u00e9", "tags": ["a", true, null], "size": -12, "ratio": 2.5e-1, "nested": {}}"#;
+        let tokens = Lexer::new().get_tokens(source).unwrap();
+        let mut syntax_analyser = SyntaxAnalyzer::new();
+
+        // Act
+        let result = syntax_analyser.parse(tokens).map(DeepDrop::into_inner);
+
+        //Assert
+        assert_eq!(result, Ok(JsonValue::Object(vec![
+            ("name".to_string(), JsonValue::String("caf\u{e9}".to_string())),
+            ("tags".to_string(), JsonValue::Array(vec![JsonValue::String("a".to_string()), JsonValue::Bool(true), JsonValue::Null])),
+            ("size".to_string(), JsonValue::Number(Number::Integer(-12))),
+            ("ratio".to_string(), JsonValue::Number(Number::Float(0.25))),
+            ("nested".to_string(), JsonValue::Object(vec![])),
+        ])));
+    }
+
+    #[test]
+    fn test_parse_should_keep_key_insertion_order() {
+        //Arrange
+        let tokens = Lexer::new().get_tokens(r#"{"z": 1, "a": [[], [2]], "m": {"y": 3, "b": 4}}"#).unwrap();
+        let mut syntax_analyser = SyntaxAnalyzer::new();
+
+        // Act
+        let value = syntax_analyser.parse(tokens).unwrap();
+
+        //Assert
+        let JsonValue::Object(members) = &*value else { panic!("expected an object") };
+        let keys: Vec<&str> = members.iter().map(|(key, _)| key.as_str()).collect();
+        assert_eq!(keys, vec!["z", "a", "m"]);
+        assert_eq!(value.get("a"), Some(&JsonValue::Array(vec![JsonValue::Array(vec![]), JsonValue::Array(vec![JsonValue::Number(Number::Integer(2))])])));
+        assert_eq!(value.get("m").and_then(|m| m.get("b")), Some(&JsonValue::Number(Number::Integer(4))));
+    }
+
+    #[test]
+    fn test_parse_should_report_position_and_expected_tokens() {
+        //Arrange
+        let tokens = Lexer::new().get_tokens("{\n  \"a\": 1\n  \"b\": 2\n}").unwrap();
+        let mut syntax_analyser = SyntaxAnalyzer::new();
+
+        // Act
+        let error = syntax_analyser.parse(tokens).unwrap_err();
+
+        //Assert
+        assert_eq!(error.error_type, ErrorType::UnexpectedToken);
+        assert_eq!((error.line, error.column, error.span.clone()), (3, 3, 13..16));
+        assert_eq!(error.expected, &[TokenType::Comma, TokenType::RightBrace]);
+        assert_eq!(error.message(), "expected `,` or `}`, found `\"b\"`");
+    }
+
+    #[test]
+    fn test_parse_should_report_first_trailing_token() {
+        //Arrange
+        let tokens = Lexer::new().get_tokens("[1] [2]").unwrap();
+        let mut syntax_analyser = SyntaxAnalyzer::new();
+
+        // Act
+        let error = syntax_analyser.parse(tokens).unwrap_err();
+
+        //Assert
+        assert_eq!(error.error_type, ErrorType::TrailingTokens);
+        assert_eq!((error.line, error.column, error.span.clone()), (1, 5, 4..5));
+        assert_eq!(error.message(), "expected end of input, found `[`");
+    }
+
+    #[test]
+    fn test_parse_should_report_unexpected_end() {
+        //Arrange
+        let tokens = Lexer::new().get_tokens("{\"a\":").unwrap();
+        let mut syntax_analyser = SyntaxAnalyzer::new();
+
+        // Act
+        let error = syntax_analyser.parse(tokens).unwrap_err();
+
+        //Assert
+        assert_eq!(error.error_type, ErrorType::UnexpectedEnd);
+        assert_eq!((error.token.clone(), error.line, error.column, error.span.clone()), (None, 1, 6, 5..5));
+        assert_eq!(error.message(), "expected string, number, boolean, `null`, `{` or `[`, found end of input");
+    }
+
+    #[test]
+    fn test_parse_should_build_deeply_nested_value() {
+        //Arrange
+        let depth = 100_000;
+        let source = format!("{}1{}", "[{\"a\":".repeat(depth), "}]".repeat(depth));
+        let tokens = Lexer::new().get_tokens(&source).unwrap();
+        let mut syntax_analyser = SyntaxAnalyzer::new();
+
+        // Act
+        let result = syntax_analyser.parse(tokens);
+
+        //Assert
+        assert!(result.is_ok());
+    }
+
 }
\ No newline at end of file
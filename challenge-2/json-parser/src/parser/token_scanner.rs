@@ -7,26 +7,27 @@ pub struct TokenScanner<'a> {
     length: usize,
 }
 impl TokenScanner<'_> {
-    pub fn new(tokens: &Vec<Token>) -> TokenScanner {
+    pub fn new(tokens: &Vec<Token>) -> TokenScanner<'_> {
         TokenScanner {
             tokens,
             cursor: 0,
             length: tokens.len(),
         }
     }
+    // None once the scanner has advanced past the last token.
     pub fn current(&self) -> Option<Token> {
-        return Some(self.tokens[self.cursor].clone());
+        return self.tokens.get(self.cursor).cloned();
     }
     pub fn is_at_end(&self) -> bool {
         return self.cursor >= self.length-1;
     }
     pub fn advance(&mut self) -> Option<Token> {
-        if self.is_at_end() {
+        if self.cursor >= self.length {
             return None;
         }
 
         self.cursor+=1;
-        return Some(self.tokens[self.cursor].clone());
+        return self.current();
     }
 }
 
@@ -40,10 +41,10 @@ mod tests {
     fn test_is_at_end() {
         // Arrange
         let tokens = &vec![ 
-            Token { token_type: TokenType::Boolean, lexeme: "true".to_string(), line: 1, position_start: 0, position_end: 0 },
-            Token { token_type: TokenType::Boolean, lexeme: "true".to_string(), line: 1, position_start: 0, position_end: 0 },
-            Token { token_type: TokenType::Boolean, lexeme: "true".to_string(), line: 1, position_start: 0, position_end: 0 },
-            Token { token_type: TokenType::Boolean, lexeme: "true".to_string(), line: 1, position_start: 0, position_end: 0 },
+            Token { token_type: TokenType::Boolean, lexeme: "true".to_string(), line: 1, column: 0, position_start: 0, position_end: 0 },
+            Token { token_type: TokenType::Boolean, lexeme: "true".to_string(), line: 1, column: 0, position_start: 0, position_end: 0 },
+            Token { token_type: TokenType::Boolean, lexeme: "true".to_string(), line: 1, column: 0, position_start: 0, position_end: 0 },
+            Token { token_type: TokenType::Boolean, lexeme: "true".to_string(), line: 1, column: 0, position_start: 0, position_end: 0 },
         ];
         let mut scanner = TokenScanner::new(tokens);
 
@@ -60,10 +61,10 @@ mod tests {
     fn test_is_at_end2() {
         // Arrange
         let tokens = &vec![ 
-            Token { token_type: TokenType::Boolean, lexeme: "true".to_string(), line: 1, position_start: 0, position_end: 0 },
-            Token { token_type: TokenType::Boolean, lexeme: "true".to_string(), line: 1, position_start: 0, position_end: 0 },
-            Token { token_type: TokenType::Boolean, lexeme: "true".to_string(), line: 1, position_start: 0, position_end: 0 },
-            Token { token_type: TokenType::Boolean, lexeme: "true".to_string(), line: 1, position_start: 0, position_end: 0 },
+            Token { token_type: TokenType::Boolean, lexeme: "true".to_string(), line: 1, column: 0, position_start: 0, position_end: 0 },
+            Token { token_type: TokenType::Boolean, lexeme: "true".to_string(), line: 1, column: 0, position_start: 0, position_end: 0 },
+            Token { token_type: TokenType::Boolean, lexeme: "true".to_string(), line: 1, column: 0, position_start: 0, position_end: 0 },
+            Token { token_type: TokenType::Boolean, lexeme: "true".to_string(), line: 1, column: 0, position_start: 0, position_end: 0 },
         ];
         let mut scanner = TokenScanner::new(tokens);
 
@@ -23,6 +23,22 @@ impl TokenType {
             _ => false,
         }
     }
+
+    // How the token is named in error messages.
+    pub fn description(&self) -> &'static str {
+        match self {
+            TokenType::LeftBrace => "`{`",
+            TokenType::RightBrace => "`}`",
+            TokenType::LeftBracket => "`[`",
+            TokenType::RightBracket => "`]`",
+            TokenType::Colon => "`:`",
+            TokenType::Comma => "`,`",
+            TokenType::String => "string",
+            TokenType::Number => "number",
+            TokenType::Boolean => "boolean",
+            TokenType::Null => "`null`",
+        }
+    }
 }
 impl fmt::Display for TokenType {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -41,11 +57,14 @@ impl fmt::Display for TokenType {
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, PartialEq, Eq, Debug)]
 pub struct Token {
     pub token_type: TokenType,
     pub lexeme: String,
     pub line: usize,
+    // The character the token starts at in its line, counting from 1.
+    pub column: usize,
+    // Byte offsets of the token in the source.
     pub position_start: usize,
     pub position_end: usize,
 }
@@ -63,6 +82,8 @@ impl fmt::Display for Token {
 pub struct ScanError {
     pub line: usize,
     pub line_start: usize,
+    // The character the lexeme starts at in its line, counting from 1.
+    pub column: usize,
     pub lexeme: String,
     pub position_start: usize,
     pub position_end: usize,
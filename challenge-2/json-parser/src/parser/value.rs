@@ -0,0 +1,253 @@
+use std::collections::HashMap;
+use std::ops::Deref;
+
+use super::types::{Token, TokenType};
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum Number {
+    Integer(i64),
+    Float(f64),
+    // A number too large or too small for a float, such as 1e400, kept as it was written so it
+    // can be written back out unchanged.
+    Literal(String),
+}
+impl Number {
+    // A literal that overflows a float is infinite, and one that underflows is zero. Text that
+    // isn't a number at all is NaN.
+    pub fn as_f64(&self) -> f64 {
+        match self {
+            Number::Integer(integer) => *integer as f64,
+            Number::Float(float) => *float,
+            Number::Literal(lexeme) => lexeme.parse().unwrap_or(f64::NAN),
+        }
+    }
+
+    // Numbers without a fraction or exponent are kept as integers when they fit, so large ids
+    // don't lose precision. "-0" is a float, to keep its sign.
+    pub fn from_lexeme(lexeme: &str) -> Number {
+        if !lexeme.contains(['.', 'e', 'E']) && lexeme != "-0" {
+            if let Ok(integer) = lexeme.parse::<i64>() {
+                return Number::Integer(integer);
+            }
+        }
+        match lexeme.parse::<f64>() {
+            Ok(float) if float.is_finite() && (float != 0.0 || !has_significant_digit(lexeme)) => Number::Float(float),
+            _ => Number::Literal(lexeme.to_string()),
+        }
+    }
+}
+
+// Whether the digits before the exponent are not all zero, so the number itself isn't zero.
+fn has_significant_digit(lexeme: &str) -> bool {
+    lexeme.split(['e', 'E']).next().unwrap_or_default().contains(|c: char| ('1'..='9').contains(&c))
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum JsonValue {
+    Null,
+    Bool(bool),
+    Number(Number),
+    String(String),
+    Array(Vec<JsonValue>),
+    // Members are kept in the order their keys first appear. A repeated key replaces the value
+    // but keeps its place.
+    Object(Vec<(String, JsonValue)>),
+}
+impl JsonValue {
+    // The value of `key` when this is an object.
+    pub fn get(&self, key: &str) -> Option<&JsonValue> {
+        match self {
+            JsonValue::Object(members) => members.iter().find(|(name, _)| name == key).map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    // The value of a literal token.
+    pub fn from_literal(token: &Token) -> Option<JsonValue> {
+        match token.token_type {
+            TokenType::Null => Some(JsonValue::Null),
+            TokenType::Boolean => Some(JsonValue::Bool(token.lexeme == "true")),
+            TokenType::Number => Some(JsonValue::Number(Number::from_lexeme(&token.lexeme))),
+            TokenType::String => Some(JsonValue::String(unescape(&token.lexeme))),
+            _ => None,
+        }
+    }
+}
+
+// Owns a value and drops it one level at a time rather than recursively, for the same reason the
+// tree is built without recursion. `JsonValue` doesn't do this itself, so that it can still be
+// taken apart with `match`, so `SyntaxAnalyzer::parse` returns its value in one of these.
+#[derive(PartialEq, Debug)]
+pub struct DeepDrop(JsonValue);
+
+impl DeepDrop {
+    pub fn new(value: JsonValue) -> DeepDrop {
+        DeepDrop(value)
+    }
+
+    pub fn into_inner(mut self) -> JsonValue {
+        std::mem::replace(&mut self.0, JsonValue::Null)
+    }
+}
+
+impl Deref for DeepDrop {
+    type Target = JsonValue;
+
+    fn deref(&self) -> &JsonValue {
+        &self.0
+    }
+}
+
+impl Drop for DeepDrop {
+    fn drop(&mut self) {
+        let mut children = vec![];
+        take_children(&mut self.0, &mut children);
+        while let Some(mut child) = children.pop() {
+            take_children(&mut child, &mut children);
+        }
+    }
+}
+
+fn take_children(value: &mut JsonValue, children: &mut Vec<JsonValue>) {
+    match value {
+        JsonValue::Array(items) => children.append(items),
+        JsonValue::Object(members) => children.extend(members.drain(..).map(|(_, value)| value)),
+        _ => {}
+    }
+}
+
+// Adds a member to an object being built. `positions` maps each key already in `members` to its
+// index, so a repeated key is found without searching the members.
+pub(super) fn insert_member(members: &mut Vec<(String, JsonValue)>, positions: &mut HashMap<String, usize>, key: String, value: JsonValue) {
+    match positions.get(&key) {
+        Some(&position) => members[position].1 = value,
+        None => {
+            positions.insert(key.clone(), members.len());
+            members.push((key, value));
+        }
+    }
+}
+
+// The text of a string lexeme, without its quotes and with escapes replaced. The lexer has
+// already checked the escapes. A \u escape of half a surrogate pair on its own becomes U+FFFD.
+pub fn unescape(lexeme: &str) -> String {
+    let inner = lexeme.strip_prefix('"').unwrap_or(lexeme);
+    let inner = inner.strip_suffix('"').unwrap_or(inner);
+
+    let mut text = String::with_capacity(inner.len());
+    let mut chars = inner.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            text.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('b') => text.push('\u{8}'),
+            Some('f') => text.push('\u{c}'),
+            Some('n') => text.push('\n'),
+            Some('r') => text.push('\r'),
+            Some('t') => text.push('\t'),
+            Some('u') => {
+                let high = hex_code(&mut chars);
+                let code = if (0xD800..0xDC00).contains(&high) && chars.peek() == Some(&'\\') {
+                    let mut lookahead = chars.clone();
+                    lookahead.next();
+                    if lookahead.next() == Some('u') {
+                        let low = hex_code(&mut lookahead);
+                        if (0xDC00..0xE000).contains(&low) {
+                            chars = lookahead;
+                            0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)
+                        } else {
+                            high
+                        }
+                    } else {
+                        high
+                    }
+                } else {
+                    high
+                };
+                text.push(char::from_u32(code).unwrap_or(char::REPLACEMENT_CHARACTER));
+            }
+            Some(other) => text.push(other),
+            None => text.push('\\'),
+        }
+    }
+    text
+}
+
+fn hex_code(chars: &mut impl Iterator<Item = char>) -> u32 {
+    chars.take(4).fold(0, |code, c| code * 16 + c.to_digit(16).unwrap_or(0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unescape_should_replace_escapes() {
+        // Arrange
+        let lexeme = r#""a\"b\\c\/d\n\té😀\ud800x""#;
+
+        // Act
+        let text = unescape(lexeme);
+
+        // Assert
+        assert_eq!(text, "a\"b\\c/d\n\t\u{e9}\u{1F600}\u{FFFD}x");
+    }
+
+    #[test]
+    fn test_number_from_lexeme() {
+        // Arrange
+        let lexemes = ["42", "-7", "9007199254740993", "1.5", "-0", "1e3", "123456789012345678901234567890", "1e400", "-2.5E-400", "0.0e-400"];
+
+        // Act
+        let numbers: Vec<Number> = lexemes.iter().map(|lexeme| Number::from_lexeme(lexeme)).collect();
+
+        // Assert
+        assert_eq!(
+            numbers,
+            vec![
+                Number::Integer(42),
+                Number::Integer(-7),
+                Number::Integer(9007199254740993),
+                Number::Float(1.5),
+                Number::Float(-0.0),
+                Number::Float(1000.0),
+                Number::Float(1.2345678901234568e29),
+                Number::Literal("1e400".to_string()),
+                Number::Literal("-2.5E-400".to_string()),
+                Number::Float(0.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_insert_member_should_keep_first_position() {
+        // Arrange
+        let mut members = vec![];
+        let mut positions = HashMap::new();
+
+        // Act
+        insert_member(&mut members, &mut positions, "b".to_string(), JsonValue::Null);
+        insert_member(&mut members, &mut positions, "a".to_string(), JsonValue::Bool(true));
+        insert_member(&mut members, &mut positions, "b".to_string(), JsonValue::Bool(false));
+
+        // Assert
+        assert_eq!(members, vec![("b".to_string(), JsonValue::Bool(false)), ("a".to_string(), JsonValue::Bool(true))]);
+    }
+
+    #[test]
+    fn test_deep_drop_should_give_back_the_value() {
+        // Arrange
+        let value = DeepDrop::new(JsonValue::Array(vec![JsonValue::String("a".to_string())]));
+
+        // Act
+        let items = match value.into_inner() {
+            JsonValue::Array(items) => items,
+            _ => vec![],
+        };
+
+        // Assert
+        assert_eq!(items, vec![JsonValue::String("a".to_string())]);
+    }
+}
@@ -8,7 +8,7 @@ echo "┌──────────────┬────────
 echo "│ file         │ result │ output                                                                                     │"
 echo "├──────────────┼────────┼────────────────────────────────────────────────────────────────────────────────────────────┤"
 for file in $(ls ./tests/files | sort -sV) ; do
-    output=$(./target/release/json-parser ./tests/files/$file)
+    output=$(./target/release/json-parser ./tests/files/$file 2>&1 | head -1)
     result=""
     if [[ ($file =~ ^pass && $output == "ok") || ($file =~ ^fail && $output != "ok")]]; then
         result="pass"